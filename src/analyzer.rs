@@ -1,90 +1,803 @@
+use crate::declarations::Declarations;
+use crate::identity::{Directory, PersonId};
+use crate::partition::minimum_partition;
+use crate::policy::{ConflictPolicy, Verdict};
+use crate::quorum::QuorumRule;
 use crate::types::Board;
-use std::collections::HashSet;
+use crate::year::VerksamhetsAr;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct VotingGroup<'a> {
     pub boards: Vec<&'a Board>,
-    pub conflicted_members: HashSet<String>,
+    /// Jäviga i det röstande organet, ordinarie såväl som ersättare.
+    pub conflicted_members: HashSet<PersonId>,
+    /// Vilka ersättare som tjänstgör för vilka ordinarie i gruppen.
+    pub substitutions: Vec<Substitution>,
+    /// Röstberättigade i gruppen, inklusive tjänstgörande ersättare.
+    pub eligible: usize,
+    /// Röstberättigade utöver kvorum.
+    pub margin: usize,
+    /// Röstberättigade vars avgång ensam gör gruppen icke beslutsmässig, i
+    /// samma ordning som `voters`.
+    pub breaking: Vec<PersonId>,
+    /// De röstberättigade: ojäviga närvarande ordinarie i rosterns ordning,
+    /// följda av tjänstgörande ersättare.
+    pub voters: Vec<PersonId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitution {
+    pub deputy: PersonId,
+    /// `None` när ersättaren går in på ett vakant mandat.
+    pub replaces: Option<PersonId>,
+}
+
+/// En koppling mellan någon i det röstande organet och en granskad styrelse,
+/// och vad jävspolicyn säger om den.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictCause {
+    pub person: PersonId,
+    pub reason: ConflictReason,
+    pub verdict: Verdict,
+    /// Regeln som gav utfallet, eller `None` för policyns standardutfall.
+    /// Deklarerade relationer ger alltid jäv utan regel.
+    pub rule: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConflictReason {
+    /// Personen har själv posten i styrelsen.
+    Membership { position: String },
+    /// En relation från relationsfilen, se `declarations::Declarations`.
+    Declared {
+        reason: String,
+        /// Den närstående i styrelsen, för relationer till en person.
+        related: Option<String>,
+        /// Den närståendes post.
+        position: Option<String>,
+    },
+}
+
+impl ConflictReason {
+    /// "Kassör" eller "Sambo: Johan Berg, Kassör"
+    pub fn describe(&self) -> String {
+        match self {
+            ConflictReason::Membership { position } => position.clone(),
+            ConflictReason::Declared {
+                reason,
+                related: Some(related),
+                position: Some(position),
+            } => format!("{}: {}, {}", reason, related, position),
+            ConflictReason::Declared { reason, .. } => reason.clone(),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct AnalysisResult<'a> {
     pub groups: Vec<VotingGroup<'a>>,
     pub impossible: Vec<&'a Board>,
+    /// Jäviga per granskad styrelse, nyckel (namn, år).
+    pub board_conflicts: HashMap<(String, VerksamhetsAr), Vec<PersonId>>,
+    /// Alla namnträffar per granskad styrelse med policyns utfall, i
+    /// styrelsens ordning.
+    pub board_causes: HashMap<(String, VerksamhetsAr), Vec<ConflictCause>>,
+    /// Visningsnamn för alla identiteter i det röstande organet.
+    pub names: HashMap<PersonId, String>,
+    /// Mandat som kvorum räknas mot: deklarerat antal eller rosterns poster.
     pub total_seats: usize,
+    pub seats: SeatSummary,
     pub present_count: usize,
+    /// Röstberättigade när ingen är jävig: närvarande ordinarie plus de
+    /// ersättare som går in för frånvarande och på vakanta mandat.
+    pub seated: usize,
     pub quorum_limit: usize,
+    pub quorum_rule: QuorumRule,
+    /// `false` om tidsbudgeten tog slut och grupperna kan vara fler än nödvändigt.
+    pub optimal: bool,
+}
+
+impl AnalysisResult<'_> {
+    /// Visningsnamnet för en identitet i det röstande organet.
+    pub fn name_of<'n>(&'n self, id: &'n PersonId) -> &'n str {
+        self.names
+            .get(id)
+            .map(String::as_str)
+            .unwrap_or(id.as_str())
+    }
+
+    /// Sant om tillräckligt många är röstberättigade, med ersättare, för att
+    /// mötet ska vara beslutsmässigt.
+    pub fn quorate(&self) -> bool {
+        self.seated >= self.quorum_limit
+    }
+
+    /// Jäviga på en granskad styrelse.
+    pub fn conflicts_of(&self, board: &Board) -> &[PersonId] {
+        self.board_conflicts
+            .get(&(board.name.clone(), board.year))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Namnträffar på en granskad styrelse och policyns utfall för dem.
+    pub fn causes_of(&self, board: &Board) -> &[ConflictCause] {
+        self.board_causes
+            .get(&(board.name.clone(), board.year))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+}
+
+/// Hur rostern för det röstande organet förhåller sig till mandatantalet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SeatSummary {
+    /// Mandatantal från stadgarna (flagga eller `Board.seats`), om angivet.
+    pub declared: Option<usize>,
+    /// Antal poster i rostern, inklusive vakanta.
+    pub roster: usize,
+    pub vacant: usize,
+    /// Namn som förekommer på flera poster och därför inte kan särskiljas.
+    pub duplicate_names: Vec<String>,
+}
+
+impl SeatSummary {
+    /// Sant om rostern och det deklarerade mandatantalet inte stämmer överens.
+    pub fn mismatch(&self) -> bool {
+        self.declared.is_some_and(|d| d != self.roster)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalysisOptions {
+    pub quorum_rule: QuorumRule,
+    /// Ersätter det mandatantal som anges på det röstande organet.
+    pub seats: Option<usize>,
+    /// Aliasregister som avgör vilka namn som är samma person.
+    pub directory: Directory,
+    /// Maximal söktid för den exakta gruppindelningen.
+    pub time_budget: Duration,
+    /// Röstberättigade utöver kvorum som varje grupp helst ska ha. Styrelser
+    /// som inte når marginalen ens ensamma grupperas utan att marginalen
+    /// sjunker under vad de klarar själva.
+    pub min_margin: usize,
+    /// Vilka poster som ger jäv, se `policy::ConflictPolicy`.
+    pub policy: ConflictPolicy,
+    /// Relationer som ger jäv utöver posterna i rostrarna.
+    pub declarations: Declarations,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        AnalysisOptions {
+            quorum_rule: QuorumRule::default(),
+            seats: None,
+            directory: Directory::default(),
+            time_budget: Duration::from_secs(2),
+            min_margin: 0,
+            policy: ConflictPolicy::default(),
+            declarations: Declarations::default(),
+        }
+    }
 }
 
 pub fn analyze_voting_groups<'a>(
     fum: &Board,
     all_boards: &'a [&'a Board],
-    absent_members: &HashSet<String>,
+    absent_members: &HashSet<PersonId>,
+) -> AnalysisResult<'a> {
+    analyze_voting_groups_with(fum, all_boards, absent_members, &AnalysisOptions::default())
+}
+
+pub fn analyze_voting_groups_with<'a>(
+    fum: &Board,
+    all_boards: &'a [&'a Board],
+    absent_members: &HashSet<PersonId>,
+    options: &AnalysisOptions,
 ) -> AnalysisResult<'a> {
-    let fum_names_all: HashSet<String> = fum.members.iter().map(|m| m.name.clone()).collect();
-    let total_seats = fum_names_all.len();
+    let roster = VotingRoster::new(fum, options);
+    let is_absent = |id: &PersonId| absent_members.contains(id);
+    let seating = roster.seating(&is_absent);
+    let present_count = seating.present;
+    let quorum_limit = seating.quorum;
 
-    let quorum_limit = (total_seats as f64 / 2.0).ceil() as usize;
+    let present_fum_ids: HashSet<PersonId> = roster
+        .members
+        .iter()
+        .filter(|id| !is_absent(id))
+        .cloned()
+        .collect();
+    let present_deputies: Vec<PersonId> = roster
+        .deputies
+        .iter()
+        .filter(|id| !is_absent(id))
+        .cloned()
+        .collect();
 
-    let present_fum_names: HashSet<String> =
-        fum_names_all.difference(absent_members).cloned().collect();
+    let voting_body: HashSet<&PersonId> = roster.members.iter().chain(&roster.deputies).collect();
+    let count = |conflicts: &HashSet<PersonId>| roster.conflicted(&is_absent, conflicts);
 
-    let present_count = present_fum_names.len();
+    // Om en röstberättigad lämnar mötet ersätts hen av en ledig ersättare
+    // om det finns någon, och kvorum kan ändras med antalet närvarande.
+    let fits_without = |leaving: &PersonId, conflicts: &HashSet<PersonId>| {
+        roster.fits(&|id: &PersonId| is_absent(id) || id == leaving, conflicts)
+    };
 
-    let mut voting_groups: Vec<VotingGroup> = Vec::new();
     let mut impossible_boards: Vec<&Board> = Vec::new();
+    let mut board_conflicts: HashMap<(String, VerksamhetsAr), Vec<PersonId>> = HashMap::new();
+    let mut board_causes: HashMap<(String, VerksamhetsAr), Vec<ConflictCause>> = HashMap::new();
+    let mut candidates: Vec<(&Board, HashSet<PersonId>)> = Vec::new();
 
-    let targets: Vec<&&Board> = all_boards.into_iter().collect();
+    for target_board in all_boards.iter() {
+        // Det röstande organet granskar inte sig självt
+        if target_board.name == fum.name && target_board.year == fum.year {
+            continue;
+        }
 
-    for target_board in targets {
-        let conflicts: HashSet<String> = target_board
-            .members
-            .iter()
-            .filter(|m| fum_names_all.contains(&m.name))
-            .map(|m| m.name.clone())
+        let causes: Vec<ConflictCause> = judge_board(target_board, &fum.name, options)
+            .into_iter()
+            .filter(|c| voting_body.contains(&c.person))
             .collect();
+        let mut ordered: Vec<PersonId> = Vec::new();
+        for c in &causes {
+            if c.verdict == Verdict::Conflict && !ordered.contains(&c.person) {
+                ordered.push(c.person.clone());
+            }
+        }
+        let conflicts: HashSet<PersonId> = ordered.iter().cloned().collect();
+        let key = (target_board.name.clone(), target_board.year);
+        board_conflicts.insert(key.clone(), ordered);
+        board_causes.insert(key, causes);
 
-        let conflicts_present: HashSet<_> = conflicts.intersection(&present_fum_names).collect();
-        let eligible_voters = present_count - conflicts_present.len();
+        let (conflicted_members, conflicted_deputies) = count(&conflicts);
 
-        if eligible_voters < quorum_limit {
+        if !seating.fits(conflicted_members, conflicted_deputies) {
             impossible_boards.push(target_board);
             continue;
         }
 
-        let mut placed = false;
-        for group in &mut voting_groups {
-            let union_conflicts: HashSet<_> = group
-                .conflicted_members
-                .union(&conflicts)
-                .cloned()
-                .collect();
-
-            let union_conflicts_present: HashSet<_> =
-                union_conflicts.intersection(&present_fum_names).collect();
-            let remaining_voters = present_count - union_conflicts_present.len();
+        candidates.push((target_board, conflicts));
+    }
 
-            if remaining_voters >= quorum_limit {
-                group.boards.push(target_board);
-                group.conflicted_members = union_conflicts;
-                placed = true;
-                break;
+    // Varje närvarande jävig ledamot eller ersättare får en bit i en mask per styrelse.
+    let mut bit_of: HashMap<&PersonId, usize> = HashMap::new();
+    for (_, conflicts) in &candidates {
+        for id in conflicts {
+            if present_fum_ids.contains(id) || present_deputies.contains(id) {
+                let next = bit_of.len();
+                bit_of.entry(id).or_insert(next);
             }
         }
-
-        if !placed {
-            voting_groups.push(VotingGroup {
-                boards: vec![target_board],
-                conflicted_members: conflicts,
-            });
-        }
     }
 
+    let (partition, optimal) = if bit_of.len() <= 128 {
+        let deputy_bits = bit_of
+            .iter()
+            .filter(|(id, _)| !present_fum_ids.contains(**id))
+            .fold(0u128, |acc, (_, &bit)| acc | (1 << bit));
+        let spare = |mask: u128| {
+            seating.fits_with_margin(
+                (mask & !deputy_bits).count_ones() as usize,
+                (mask & deputy_bits).count_ones() as usize,
+                options.min_margin,
+            )
+        };
+        let masks: Vec<u128> = candidates
+            .iter()
+            .map(|(_, conflicts)| {
+                conflicts
+                    .iter()
+                    .filter_map(|id| bit_of.get(id))
+                    .fold(0u128, |acc, &bit| acc | (1 << bit))
+            })
+            .collect();
+        // En styrelse som inte når marginalen ensam får bara dela grupp med
+        // styrelser vars jäv redan ingår i dess eget.
+        let weak: Vec<u128> = masks.iter().copied().filter(|&m| !spare(m)).collect();
+        let fits = |mask: u128| spare(mask) || weak.iter().any(|&w| mask & w == mask);
+        let p = minimum_partition(&masks, &fits, options.time_budget);
+        (p.groups, p.optimal)
+    } else {
+        let spare = |conflicts: &HashSet<PersonId>| {
+            let (m, d) = count(conflicts);
+            seating.fits_with_margin(m, d, options.min_margin)
+        };
+        let weak: Vec<&HashSet<PersonId>> = candidates
+            .iter()
+            .map(|(_, conflicts)| conflicts)
+            .filter(|c| !spare(c))
+            .collect();
+        let fits = |conflicts: &HashSet<PersonId>| {
+            spare(conflicts) || weak.iter().any(|w| conflicts.is_subset(w))
+        };
+        (greedy_by_sets(&candidates, &fits), false)
+    };
+
+    let voting_groups = partition
+        .into_iter()
+        .map(|indices| {
+            let mut group = VotingGroup {
+                boards: Vec::new(),
+                conflicted_members: HashSet::new(),
+                substitutions: Vec::new(),
+                eligible: 0,
+                margin: 0,
+                breaking: Vec::new(),
+                voters: Vec::new(),
+            };
+            for i in indices {
+                let (board, conflicts) = &candidates[i];
+                group.boards.push(*board);
+                group.conflicted_members.extend(conflicts.iter().cloned());
+            }
+
+            // Frånvarande ordinarie, vakanta mandat och jäviga ordinarie
+            // ersätts i den ordningen av ojäviga ersättare i prioritetsordning.
+            let absent_members = roster
+                .members
+                .iter()
+                .filter(|id| !present_fum_ids.contains(*id));
+            let vacant = seating.open - absent_members.clone().count();
+            let vacated = absent_members
+                .map(Some)
+                .chain(std::iter::repeat_n(None, vacant))
+                .chain(
+                    roster
+                        .members
+                        .iter()
+                        .filter(|id| present_fum_ids.contains(*id))
+                        .filter(|id| group.conflicted_members.contains(*id))
+                        .map(Some),
+                );
+            let available = present_deputies
+                .iter()
+                .filter(|d| !group.conflicted_members.contains(*d));
+            group.substitutions = available
+                .zip(vacated)
+                .map(|(deputy, replaces)| Substitution {
+                    deputy: deputy.clone(),
+                    replaces: replaces.cloned(),
+                })
+                .collect();
+
+            let (m, d) = count(&group.conflicted_members);
+            group.eligible = seating.eligible(m, d);
+            group.voters = roster
+                .members
+                .iter()
+                .filter(|id| present_fum_ids.contains(*id))
+                .filter(|id| !group.conflicted_members.contains(*id))
+                .chain(group.substitutions.iter().map(|s| &s.deputy))
+                .cloned()
+                .collect();
+            group.margin = group.eligible - seating.quorum;
+            group.breaking = group
+                .voters
+                .iter()
+                .filter(|v| !fits_without(v, &group.conflicted_members))
+                .cloned()
+                .collect();
+            group
+        })
+        .collect();
+
     AnalysisResult {
         groups: voting_groups,
         impossible: impossible_boards,
-        total_seats,
+        board_conflicts,
+        board_causes,
+        names: roster.names,
+        total_seats: roster.total_seats,
+        seats: roster.seats,
         present_count,
+        seated: seating.eligible(0, 0),
         quorum_limit,
+        quorum_rule: options.quorum_rule,
+        optimal,
+    }
+}
+
+/// Vilka frånvarande som behöver kallas in för att styrelser som inte kan
+/// behandlas ska bli möjliga att besluta om.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallInPlan {
+    pub boards: Vec<BoardCallIn>,
+    /// Minsta mängd frånvarande vars ankomst räddar alla styrelserna på en
+    /// gång.
+    pub all: CallIn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardCallIn {
+    pub name: String,
+    pub year: VerksamhetsAr,
+    /// Minsta mängd ojäviga frånvarande vars ankomst gör styrelsen möjlig att
+    /// besluta om.
+    pub call: CallIn,
+}
+
+/// Utfallet av en sökning efter vilka som ska ringas in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "people", rename_all = "snake_case")]
+pub enum CallIn<P = PersonId> {
+    /// Den minsta mängden, med den tidigaste i rostern först bland lika stora.
+    Found(Vec<P>),
+    /// Inte ens om alla frånvarande kommer går det att besluta.
+    Unreachable,
+    /// Sökningen nådde `CALL_IN_SEARCH_LIMIT` utan att hitta en mindre
+    /// mängd. Alla frånvarande räcker, och de är mängden som anges.
+    SearchExhausted(Vec<P>),
+}
+
+impl<P> CallIn<P> {
+    pub fn map<Q>(&self, f: impl Fn(&P) -> Q) -> CallIn<Q> {
+        match self {
+            CallIn::Found(people) => CallIn::Found(people.iter().map(f).collect()),
+            CallIn::Unreachable => CallIn::Unreachable,
+            CallIn::SearchExhausted(people) => {
+                CallIn::SearchExhausted(people.iter().map(f).collect())
+            }
+        }
+    }
+}
+
+/// Minsta mängder frånvarande ledamöter och ersättare att ringa in för
+/// styrelserna i `impossible`.
+///
+/// Om en styrelse kan behandlas beror bara på hur många ojäviga ordinarie
+/// och ersättare som är på plats, så för en enskild styrelse räknas det
+/// minsta antalet fram med samma `Seating` som analysen använder. För alla
+/// styrelser på en gång spelar det roll vem som kommer, och mängderna prövas
+/// i storleksordning upp till `CALL_IN_SEARCH_LIMIT`.
+pub fn suggest_call_ins(
+    fum: &Board,
+    impossible: &[&Board],
+    absent_members: &HashSet<PersonId>,
+    options: &AnalysisOptions,
+) -> CallInPlan {
+    let roster = VotingRoster::new(fum, options);
+    let absent: Vec<PersonId> = roster
+        .members
+        .iter()
+        .chain(&roster.deputies)
+        .filter(|id| absent_members.contains(*id))
+        .cloned()
+        .collect();
+    let conflicts: Vec<HashSet<PersonId>> = impossible
+        .iter()
+        .map(|board| {
+            judge_board(board, &fum.name, options)
+                .into_iter()
+                .filter(|c| c.verdict == Verdict::Conflict)
+                .map(|c| c.person)
+                .collect()
+        })
+        .collect();
+    let decidable = |conflicts: &HashSet<PersonId>, arriving: &[PersonId]| {
+        roster.fits(
+            &|id: &PersonId| absent_members.contains(id) && !arriving.contains(id),
+            conflicts,
+        )
+    };
+
+    let boards: Vec<BoardCallIn> = impossible
+        .iter()
+        .zip(&conflicts)
+        .map(|(board, conflicts)| {
+            // Jäviga som kommer ger ingen röst, så bara ojäviga räknas. Bland
+            // lika många prövas först så många ordinarie som möjligt.
+            let members: Vec<&PersonId> = absent
+                .iter()
+                .filter(|id| roster.members.contains(*id) && !conflicts.contains(*id))
+                .collect();
+            let deputies: Vec<&PersonId> = absent
+                .iter()
+                .filter(|id| roster.deputies.contains(*id) && !conflicts.contains(*id))
+                .collect();
+            let call = (0..=members.len() + deputies.len())
+                .flat_map(|size| {
+                    (size.saturating_sub(deputies.len())..=size.min(members.len()))
+                        .rev()
+                        .map(move |m| (m, size - m))
+                })
+                .map(|(m, d)| {
+                    members[..m]
+                        .iter()
+                        .chain(&deputies[..d])
+                        .map(|id| (*id).clone())
+                        .collect::<Vec<_>>()
+                })
+                .find(|arriving| decidable(conflicts, arriving));
+            BoardCallIn {
+                name: board.name.clone(),
+                year: board.year,
+                call: call.map_or(CallIn::Unreachable, CallIn::Found),
+            }
+        })
+        .collect();
+
+    let all = if !conflicts.iter().all(|c| decidable(c, &absent)) {
+        CallIn::Unreachable
+    } else {
+        // Ingen mängd kan vara mindre än den största enskilda styrelsens.
+        let least = boards
+            .iter()
+            .filter_map(|b| match &b.call {
+                CallIn::Found(people) => Some(people.len()),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        smallest_subset(&absent, least, |arriving| {
+            conflicts.iter().all(|c| decidable(c, arriving))
+        })
+        .map_or(CallIn::SearchExhausted(absent.clone()), CallIn::Found)
+    };
+
+    CallInPlan { boards, all }
+}
+
+/// Högsta antal kandidatmängder som prövas när alla styrelser ska räddas på
+/// en gång. Med ett tjugotal frånvarande blir en fullständig sökning annars
+/// för långsam.
+pub const CALL_IN_SEARCH_LIMIT: usize = 1 << 16;
+
+/// Den minsta delmängden av `pool` med minst `least` personer som uppfyller
+/// `ok`, med den tidigaste i `pool` först bland lika stora. `None` om
+/// `CALL_IN_SEARCH_LIMIT` nås först.
+///
+/// Fler närvarande gör inte alltid en styrelse möjlig (kvorum kan räknas på
+/// de närvarande), så alla storlekar prövas.
+fn smallest_subset(
+    pool: &[PersonId],
+    least: usize,
+    ok: impl Fn(&[PersonId]) -> bool,
+) -> Option<Vec<PersonId>> {
+    let mut budget = CALL_IN_SEARCH_LIMIT;
+    for size in least..=pool.len() {
+        let mut chosen = Vec::with_capacity(size);
+        if let Some(found) = first_subset_of_size(pool, size, 0, &mut chosen, &ok, &mut budget) {
+            return Some(found);
+        }
+        if budget == 0 {
+            break;
+        }
+    }
+    None
+}
+
+fn first_subset_of_size(
+    pool: &[PersonId],
+    size: usize,
+    start: usize,
+    chosen: &mut Vec<PersonId>,
+    ok: &impl Fn(&[PersonId]) -> bool,
+    budget: &mut usize,
+) -> Option<Vec<PersonId>> {
+    if chosen.len() == size {
+        if *budget == 0 {
+            return None;
+        }
+        *budget -= 1;
+        return ok(chosen).then(|| chosen.clone());
+    }
+    for i in start..pool.len() {
+        if pool.len() - i < size - chosen.len() || *budget == 0 {
+            break;
+        }
+        chosen.push(pool[i].clone());
+        if let Some(found) = first_subset_of_size(pool, size, i + 1, chosen, ok, budget) {
+            return Some(found);
+        }
+        chosen.pop();
+    }
+    None
+}
+
+/// Det röstande organets ordinarie och ersättare som identiteter, och hur
+/// många som får rösta vid en viss närvaro. Analysen och förslagen på vilka
+/// som ska ringas in räknar båda härifrån.
+struct VotingRoster {
+    /// Ordinarie i rosterns ordning. Samma identitet på flera poster räknas en gång.
+    members: Vec<PersonId>,
+    /// Ersättare i prioritetsordning som inte också är ordinarie.
+    deputies: Vec<PersonId>,
+    /// Visningsnamn för ordinarie och ersättare.
+    names: HashMap<PersonId, String>,
+    seats: SeatSummary,
+    /// Mandat som kvorum räknas mot: deklarerat antal eller rosterns poster.
+    total_seats: usize,
+    quorum_rule: QuorumRule,
+}
+
+impl VotingRoster {
+    fn new(fum: &Board, options: &AnalysisOptions) -> Self {
+        let directory = &options.directory;
+        let seats = seat_summary(fum, directory, options.seats);
+        let mut names: HashMap<PersonId, String> = HashMap::new();
+        let mut members: Vec<PersonId> = Vec::new();
+        let mut deputies: Vec<PersonId> = Vec::new();
+        for (m, deputy) in fum
+            .seated_members()
+            .map(|m| (m, false))
+            .chain(fum.seated_deputies().map(|m| (m, true)))
+        {
+            let id = directory.resolve(&m.name, &fum.name);
+            names
+                .entry(id.clone())
+                .or_insert_with(|| directory.display_name(&id).unwrap_or(&m.name).to_string());
+            if members.contains(&id) || deputies.contains(&id) {
+                continue;
+            }
+            if deputy {
+                deputies.push(id);
+            } else {
+                members.push(id);
+            }
+        }
+        VotingRoster {
+            members,
+            deputies,
+            names,
+            total_seats: seats.declared.unwrap_or(seats.roster),
+            seats,
+            quorum_rule: options.quorum_rule,
+        }
+    }
+
+    /// Närvaron när de som `absent` pekar ut inte är på plats.
+    fn seating(&self, absent: &impl Fn(&PersonId) -> bool) -> Seating {
+        let present = self.members.iter().filter(|id| !absent(id)).count();
+        Seating {
+            present,
+            open: self
+                .total_seats
+                .max(self.members.len())
+                .saturating_sub(present),
+            deputies: self.deputies.iter().filter(|id| !absent(id)).count(),
+            quorum: self.quorum_rule.limit(self.total_seats, present),
+        }
+    }
+
+    /// Närvarande jäviga ordinarie och ersättare.
+    fn conflicted(
+        &self,
+        absent: &impl Fn(&PersonId) -> bool,
+        conflicts: &HashSet<PersonId>,
+    ) -> (usize, usize) {
+        let count = |ids: &[PersonId]| {
+            ids.iter()
+                .filter(|id| !absent(id) && conflicts.contains(*id))
+                .count()
+        };
+        (count(&self.members), count(&self.deputies))
+    }
+
+    /// Samma prövning som gör en styrelse omöjlig i analysen.
+    fn fits(&self, absent: &impl Fn(&PersonId) -> bool, conflicts: &HashSet<PersonId>) -> bool {
+        let (members, deputies) = self.conflicted(absent, conflicts);
+        self.seating(absent).fits(members, deputies)
+    }
+}
+
+/// Policyns utfall för varje post i styrelsen, följt av de deklarerade
+/// relationer som träffar styrelsen.
+fn judge_board(board: &Board, voting_body: &str, options: &AnalysisOptions) -> Vec<ConflictCause> {
+    let directory = &options.directory;
+    let posts = board.people().map(|m| {
+        let judgement = options.policy.judge(&m.position);
+        ConflictCause {
+            person: directory.resolve(&m.name, &board.name),
+            reason: ConflictReason::Membership {
+                position: m.position.clone(),
+            },
+            verdict: judgement.verdict,
+            rule: judgement.rule.map(|r| r.label()),
+        }
+    });
+    let declared = options
+        .declarations
+        .conflicts_on(board, voting_body, directory)
+        .into_iter()
+        .map(|d| {
+            let (related, position) = d.related.unzip();
+            ConflictCause {
+                person: d.member,
+                reason: ConflictReason::Declared {
+                    reason: d.declaration.reason.clone(),
+                    related,
+                    position,
+                },
+                verdict: Verdict::Conflict,
+                rule: None,
+            }
+        });
+    posts.chain(declared).collect()
+}
+
+/// Närvaron i det röstande organet, oberoende av vilka styrelser som granskas.
+struct Seating {
+    present: usize,
+    /// Mandat utan närvarande ordinarie, frånvarande såväl som vakanta, som
+    /// ersättare kan gå in på.
+    open: usize,
+    deputies: usize,
+    quorum: usize,
+}
+
+impl Seating {
+    /// Röstberättigade när så många ordinarie och ersättare är jäviga.
+    fn eligible(&self, conflicted_members: usize, conflicted_deputies: usize) -> usize {
+        let vacated = self.open + conflicted_members;
+        let substitutes = self.deputies - conflicted_deputies;
+        self.present - conflicted_members + vacated.min(substitutes)
+    }
+
+    fn fits(&self, conflicted_members: usize, conflicted_deputies: usize) -> bool {
+        self.fits_with_margin(conflicted_members, conflicted_deputies, 0)
+    }
+
+    /// Sant om minst `margin` röstberättigade finns utöver kvorum.
+    fn fits_with_margin(
+        &self,
+        conflicted_members: usize,
+        conflicted_deputies: usize,
+        margin: usize,
+    ) -> bool {
+        self.eligible(conflicted_members, conflicted_deputies) >= self.quorum + margin
+    }
+}
+
+/// Reservväg när för många jäviga ledamöter finns för bitmaskerna.
+fn greedy_by_sets(
+    candidates: &[(&Board, HashSet<PersonId>)],
+    fits: &dyn Fn(&HashSet<PersonId>) -> bool,
+) -> Vec<Vec<usize>> {
+    let mut unions: Vec<HashSet<PersonId>> = Vec::new();
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+
+    for (i, (_, conflicts)) in candidates.iter().enumerate() {
+        let position = unions.iter().position(|u| {
+            let merged: HashSet<PersonId> = u.union(conflicts).cloned().collect();
+            fits(&merged)
+        });
+        match position {
+            Some(g) => {
+                unions[g].extend(conflicts.iter().cloned());
+                groups[g].push(i);
+            }
+            None => {
+                unions.push(conflicts.clone());
+                groups.push(vec![i]);
+            }
+        }
+    }
+    groups
+}
+
+fn seat_summary(
+    fum: &Board,
+    directory: &Directory,
+    declared_override: Option<usize>,
+) -> SeatSummary {
+    let mut seen: HashSet<PersonId> = HashSet::new();
+    let mut duplicate_names: Vec<String> = Vec::new();
+    for m in fum.seated_members() {
+        let id = directory.resolve(&m.name, &fum.name);
+        if !seen.insert(id) && !duplicate_names.contains(&m.name) {
+            duplicate_names.push(m.name.clone());
+        }
+    }
+    duplicate_names.sort();
+
+    SeatSummary {
+        declared: declared_override.or(fum.seats),
+        roster: fum.members.len(),
+        vacant: fum.members.iter().filter(|m| m.is_vacant()).count(),
+        duplicate_names,
     }
 }
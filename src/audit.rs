@@ -0,0 +1,266 @@
+//! Hela granskningen som ett bibliotek-API, utan utskrifter.
+//!
+//! ```no_run
+//! use containtment_check::audit::Audit;
+//! use containtment_check::source::load_boards;
+//!
+//! let boards = load_boards("fum.txt")?;
+//! let result = Audit::new(boards)
+//!     .base_board("Fullmäktige")
+//!     .absent(["Erik Nilsson"])
+//!     .priority(["KIDS"])
+//!     .run()?;
+//! for group in &result.report.groups {
+//!     println!("{} röstberättigade", group.eligible);
+//! }
+//! # Ok::<(), containtment_check::error::Error>(())
+//! ```
+
+use crate::analyzer::{AnalysisOptions, analyze_voting_groups_with, suggest_call_ins};
+use crate::corrections::{Correction, Suggestion, apply_corrections, find_suggestions};
+use crate::error::Error;
+use crate::identity::{Directory, PersonId};
+use crate::names::NameMatcher;
+use crate::report::Report;
+use crate::types::Board;
+use crate::year::VerksamhetsAr;
+use std::collections::HashSet;
+
+/// Bestämmer vad som ska hända med ett förslag på namnkorrigering.
+/// `Ok(None)` avvisar förslaget, `Ok(Some(namn))` byter till namnet.
+pub type CorrectionDecider<'a> = Box<dyn FnMut(&Suggestion) -> Result<Option<String>, Error> + 'a>;
+
+pub struct Audit<'a> {
+    boards: Vec<Board>,
+    base_board: String,
+    voting_year: Option<VerksamhetsAr>,
+    audit_years: Vec<VerksamhetsAr>,
+    absent: Vec<String>,
+    priority: Vec<String>,
+    le_threshold: usize,
+    options: AnalysisOptions,
+    decide: CorrectionDecider<'a>,
+}
+
+/// Resultatet av en granskning.
+#[derive(Debug, Clone)]
+pub struct AuditResult {
+    /// Det röstande organet som granskningen utgår från.
+    pub voting_body: Board,
+    /// Verksamhetsåren som granskas i tidsordning. Som standard bara året
+    /// före det röstande organets.
+    pub audited_years: Vec<VerksamhetsAr>,
+    /// Frånvarande som de står i det röstande organets roster.
+    pub absent: Vec<String>,
+    /// Angivna frånvarande som inte finns i det röstande organet.
+    pub unknown_absent: Vec<String>,
+    /// Alla förslag på namnkorrigeringar, godkända eller inte.
+    pub suggestions: Vec<Suggestion>,
+    /// De korrigeringar som tillämpades.
+    pub corrections: Vec<Correction>,
+    /// De granskade styrelserna efter korrigering, i prioritetsordning.
+    pub boards: Vec<Board>,
+    pub report: Report,
+}
+
+impl<'a> Audit<'a> {
+    /// En granskning av `boards` med standardinställningar: röstande organ
+    /// "Fullmäktige" senaste året, inga frånvarande och automatiskt godkända
+    /// korrigeringar inom tre tecken.
+    pub fn new(boards: Vec<Board>) -> Self {
+        Audit {
+            boards,
+            base_board: "Fullmäktige".to_string(),
+            voting_year: None,
+            audit_years: Vec::new(),
+            absent: Vec::new(),
+            priority: Vec::new(),
+            le_threshold: 3,
+            options: AnalysisOptions::default(),
+            decide: Box::new(|s| Ok(Some(s.candidates[0].name.clone()))),
+        }
+    }
+
+    pub fn base_board(mut self, name: impl Into<String>) -> Self {
+        self.base_board = name.into();
+        self
+    }
+
+    /// Det röstande organets verksamhetsår. Utan år används det senaste.
+    pub fn voting_year(mut self, year: VerksamhetsAr) -> Self {
+        self.voting_year = Some(year);
+        self
+    }
+
+    /// Granska flera år på en gång, t.ex. när ett äldre års ansvarsfrihet
+    /// har bordlagts. Utan år granskas året före det röstande organets.
+    pub fn audit_years(mut self, years: impl IntoIterator<Item = VerksamhetsAr>) -> Self {
+        self.audit_years = years.into_iter().collect();
+        self
+    }
+
+    pub fn absent<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.absent = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Styrelser som ska behandlas först, i första hand i samma grupp.
+    pub fn priority<I, S>(mut self, boards: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.priority = boards.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Största redigeringsavstånd för att föreslå en namnkorrigering.
+    pub fn le_threshold(mut self, threshold: usize) -> Self {
+        self.le_threshold = threshold;
+        self
+    }
+
+    pub fn options(mut self, options: AnalysisOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn decide_corrections(
+        mut self,
+        decide: impl FnMut(&Suggestion) -> Result<Option<String>, Error> + 'a,
+    ) -> Self {
+        self.decide = Box::new(decide);
+        self
+    }
+
+    pub fn run(mut self) -> Result<AuditResult, Error> {
+        let voting_body = find_voting_board(&self.boards, &self.base_board, self.voting_year)
+            .cloned()
+            .ok_or_else(|| Error::VotingBodyNotFound {
+                name: self.base_board.clone(),
+                year: self.voting_year,
+            })?;
+        let directory = &self.options.directory;
+
+        let voting_body_names: Vec<String> = voting_body.people().map(|m| m.name.clone()).collect();
+        let (absent, unknown_absent) = match_absences(&voting_body, &self.absent, directory);
+
+        let mut audited_years = if self.audit_years.is_empty() {
+            let previous = voting_body
+                .year
+                .previous()
+                .ok_or_else(|| Error::InvalidYear(voting_body.year.to_string()))?;
+            vec![previous]
+        } else {
+            self.audit_years.clone()
+        };
+        audited_years.sort();
+        audited_years.dedup();
+
+        let suggestions = find_suggestions(
+            &self.boards,
+            &voting_body_names,
+            directory,
+            &audited_years,
+            self.le_threshold,
+        );
+        let mut corrections = Vec::new();
+        for suggestion in &suggestions {
+            if let Some(to) = (self.decide)(suggestion)? {
+                corrections.push(Correction {
+                    board: suggestion.board.clone(),
+                    year: suggestion.year,
+                    from: suggestion.found.clone(),
+                    distance: directory.matcher.distance(&suggestion.found, &to),
+                    to,
+                });
+            }
+        }
+        apply_corrections(&mut self.boards, &corrections);
+
+        let mut boards: Vec<Board> = self
+            .boards
+            .iter()
+            .filter(|b| audited_years.contains(&b.year))
+            .filter(|b| !(b.name == voting_body.name && b.year == voting_body.year))
+            .cloned()
+            .collect();
+        if let Some(year) = audited_years
+            .iter()
+            .find(|y| !boards.iter().any(|b| b.year == **y))
+        {
+            return Err(Error::NoBoardsForYear(*year));
+        }
+        if !self.priority.is_empty() {
+            let board_key = |name: &str| NameMatcher::default().normalize(name);
+            let priority: HashSet<String> = self.priority.iter().map(|s| board_key(s)).collect();
+            boards.sort_by_key(|b| !priority.contains(&board_key(&b.name)));
+        }
+
+        let absent_ids: HashSet<PersonId> = absent
+            .iter()
+            .map(|n| directory.resolve(n, &voting_body.name))
+            .collect();
+        let board_refs: Vec<&Board> = boards.iter().collect();
+        let analysis =
+            analyze_voting_groups_with(&voting_body, &board_refs, &absent_ids, &self.options);
+        let mut report = Report::new(&analysis, &voting_body, &absent_ids, &corrections);
+        if !analysis.impossible.is_empty() {
+            let plan = suggest_call_ins(
+                &voting_body,
+                &analysis.impossible,
+                &absent_ids,
+                &self.options,
+            );
+            report = report.with_call_in(&plan, &analysis);
+        }
+
+        Ok(AuditResult {
+            voting_body,
+            audited_years,
+            absent,
+            unknown_absent,
+            suggestions,
+            corrections,
+            boards,
+            report,
+        })
+    }
+}
+
+/// Det röstande organet för ett visst år, eller det senaste om inget år anges.
+pub fn find_voting_board<'b>(
+    boards: &'b [Board],
+    base_name: &str,
+    requested_year: Option<VerksamhetsAr>,
+) -> Option<&'b Board> {
+    let candidates = boards.iter().filter(|b| b.name == base_name);
+    match requested_year {
+        Some(y) => candidates.into_iter().find(|b| b.year == y),
+        None => candidates.max_by_key(|b| b.year),
+    }
+}
+
+/// Delar upp angivna frånvarande i namn som de står i rostern och okända namn.
+/// Namnen slås upp i `directory`, så att alias i aliasfilen känns igen.
+pub fn match_absences(
+    voting_body: &Board,
+    requested: &[String],
+    directory: &Directory,
+) -> (Vec<String>, Vec<String>) {
+    let mut absent: Vec<String> = Vec::new();
+    let mut unknown = Vec::new();
+    for name in requested {
+        let trimmed = name.trim();
+        match directory.find_on(voting_body, trimmed) {
+            Some(m) if !absent.contains(&m.name) => absent.push(m.name.clone()),
+            Some(_) => {}
+            None => unknown.push(trimmed.to_string()),
+        }
+    }
+    (absent, unknown)
+}
@@ -0,0 +1,43 @@
+use crate::quorum::QuorumRule;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+
+/// Organisationsspecifika inställningar som läses från en TOML-fil.
+///
+/// ```toml
+/// quorum = "more-than-half"
+/// seats = 21
+/// min_margin = 1
+/// aliases = "alias.toml"
+/// fold_diacritics = false
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub quorum: Option<QuorumRule>,
+    /// Antal mandat i det röstande organet enligt stadgarna.
+    pub seats: Option<usize>,
+    /// Önskat antal röstberättigade utöver kvorum i varje grupp.
+    pub min_margin: Option<usize>,
+    /// Sökväg till aliasfilen, se `identity::Directory`.
+    pub aliases: Option<String>,
+    /// Jämför namn utan hänsyn till accenter, se `names::NameMatcher`.
+    pub fold_diacritics: Option<bool>,
+    /// Protokollmall för `--format markdown`, se `protocol::ProtocolTemplate`.
+    pub template: Option<String>,
+    /// Närvarofil för `simulate`, se `simulation::AttendanceFile`.
+    pub attendance: Option<String>,
+    /// Jävspolicy, se `policy::ConflictPolicy`.
+    pub policy: Option<String>,
+    /// Deklarerade relationer, se `declarations::Declarations`.
+    pub declarations: Option<String>,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Kunde inte läsa konfigurationsfilen {}", path))?;
+        toml::from_str(&text).with_context(|| format!("Ogiltig konfigurationsfil {}", path))
+    }
+}
@@ -0,0 +1,188 @@
+//! Förslag på namnkorrigeringar och beslut om dem.
+//!
+//! Ett stavfel i en styrelselista gör att en jävig FUM-ledamot inte upptäcks,
+//! men en felaktig korrigering slår ihop två olika personer. Förslagen tas
+//! därför fram separat och tillämpas först när de har godkänts, interaktivt
+//! eller från en fil med tidigare beslut:
+//!
+//! ```toml
+//! [[correction]]
+//! board = "KIDS"
+//! year = "2024/2025"
+//! found = "Eric Nilson"
+//! replace_with = "Erik Nilsson"
+//!
+//! [[correction]]
+//! board = "Sexmästeriet"
+//! year = "2024/2025"
+//! found = "Anna Berg"
+//! # Utan replace_with är förslaget avvisat.
+//! ```
+
+use crate::identity::Directory;
+use crate::types::Board;
+use crate::year::VerksamhetsAr;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Ett namn i en styrelse som liknar ett eller flera namn i det röstande organet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub board: String,
+    pub year: VerksamhetsAr,
+    pub found: String,
+    /// Kandidater sorterade efter avstånd, närmast först.
+    pub candidates: Vec<Candidate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub name: String,
+    pub distance: usize,
+}
+
+/// En godkänd korrigering som har tillämpats på styrelserna.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Correction {
+    pub board: String,
+    pub year: VerksamhetsAr,
+    pub from: String,
+    pub to: String,
+    pub distance: usize,
+}
+
+/// Ett sparat beslut om ett förslag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decision {
+    pub board: String,
+    pub year: VerksamhetsAr,
+    pub found: String,
+    /// `None` betyder att förslaget avvisades.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace_with: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecisionLog {
+    #[serde(default, rename = "correction")]
+    pub decisions: Vec<Decision>,
+}
+
+impl DecisionLog {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Kunde inte läsa korrigeringsfilen {}", path))?;
+        toml::from_str(&text).with_context(|| format!("Ogiltig korrigeringsfil {}", path))
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("Kunde inte skriva korrigeringsfilen {}", path))
+    }
+
+    /// Tidigare beslut för förslaget, om något.
+    pub fn find(&self, suggestion: &Suggestion, directory: &Directory) -> Option<&Decision> {
+        self.decisions.iter().find(|d| {
+            same_entry(
+                d,
+                &suggestion.board,
+                suggestion.year,
+                &suggestion.found,
+                directory,
+            )
+        })
+    }
+
+    /// Sparar ett beslut och ersätter ett tidigare beslut om samma förslag,
+    /// jämfört på samma sätt som i `find`.
+    pub fn record(&mut self, decision: Decision, directory: &Directory) {
+        self.decisions.retain(|d| {
+            !same_entry(
+                d,
+                &decision.board,
+                decision.year,
+                &decision.found,
+                directory,
+            )
+        });
+        self.decisions.push(decision);
+    }
+}
+
+fn same_entry(
+    decision: &Decision,
+    board: &str,
+    year: VerksamhetsAr,
+    found: &str,
+    directory: &Directory,
+) -> bool {
+    decision.board == board
+        && decision.year == year
+        && directory.matcher.same(&decision.found, found)
+}
+
+/// Namn på ordinarie och ersättare i styrelserna för `target_years` som
+/// ligger inom `threshold` från ett namn i `correct_names` utan att vara samma
+/// namn.
+///
+/// Namn som aliasfilen känner till räknas som avsiktliga och föreslås inte.
+pub fn find_suggestions(
+    boards: &[Board],
+    correct_names: &[String],
+    directory: &Directory,
+    target_years: &[VerksamhetsAr],
+    threshold: usize,
+) -> Vec<Suggestion> {
+    let matcher = &directory.matcher;
+    let mut suggestions = Vec::new();
+
+    for board in boards.iter().filter(|b| target_years.contains(&b.year)) {
+        for member in board.people() {
+            if correct_names.iter().any(|n| matcher.same(n, &member.name))
+                || directory.lookup(&member.name, &board.name).is_some()
+            {
+                continue;
+            }
+            let mut candidates: Vec<Candidate> = correct_names
+                .iter()
+                .map(|n| Candidate {
+                    name: n.clone(),
+                    distance: matcher.distance(&member.name, n),
+                })
+                .filter(|c| c.distance <= threshold)
+                .collect();
+            if candidates.is_empty() {
+                continue;
+            }
+            candidates.sort_by_key(|c| c.distance);
+            candidates.dedup_by(|a, b| a.name == b.name);
+            suggestions.push(Suggestion {
+                board: board.name.clone(),
+                year: board.year,
+                found: member.name.clone(),
+                candidates,
+            });
+        }
+    }
+    suggestions
+}
+
+/// Byter namn enligt godkända korrigeringar.
+pub fn apply_corrections(boards: &mut [Board], corrections: &[Correction]) {
+    for c in corrections {
+        for board in boards
+            .iter_mut()
+            .filter(|b| b.name == c.board && b.year == c.year)
+        {
+            for member in board
+                .members
+                .iter_mut()
+                .chain(board.deputies.iter_mut())
+                .filter(|m| m.name == c.from)
+            {
+                member.name = c.to.clone();
+            }
+        }
+    }
+}
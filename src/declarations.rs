@@ -0,0 +1,168 @@
+//! Deklarerade relationer som ger jäv utan att synas i rostrarna.
+//!
+//! En ledamot är också jävig när en partner eller nära släkting sitter i en
+//! granskad styrelse, eller när ledamoten är anställd av kåren. Det går inte
+//! att läsa ut från webbsidan, så relationerna anmäls i en lokal TOML-fil.
+//! Varje deklaration gäller antingen en person, oavsett vilken styrelse hen
+//! sitter i, eller en styrelse direkt:
+//!
+//! ```toml
+//! [[declaration]]
+//! member = "Anna Berg"
+//! person = "Johan Berg"
+//! reason = "Sambo"
+//!
+//! [[declaration]]
+//! member = "David Lund"
+//! board = "Kårstyrelsen"
+//! year = "2024/2025"
+//! reason = "Anställd av kåren"
+//! ```
+//!
+//! Utan `year` gäller en styrelsedeklaration alla år.
+
+use crate::identity::{Directory, PersonId};
+use crate::types::Board;
+use crate::year::VerksamhetsAr;
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::fs;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Declarations {
+    pub declarations: Vec<Declaration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+    /// Ledamoten i det röstande organet som är jävig.
+    pub member: String,
+    pub target: DeclaredTarget,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeclaredTarget {
+    /// Jäv för varje granskad styrelse där personen har en post.
+    Person(String),
+    /// Jäv för styrelsen med det namnet, det året eller alla år.
+    Board {
+        name: String,
+        year: Option<VerksamhetsAr>,
+    },
+}
+
+/// En deklarerad relation som träffar en granskad styrelse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclaredConflict<'d> {
+    pub member: PersonId,
+    pub declaration: &'d Declaration,
+    /// Personen i styrelsen och posten hen har, för personrelationer.
+    pub related: Option<(String, String)>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeclarationsFile {
+    #[serde(default, rename = "declaration")]
+    declarations: Vec<DeclarationEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeclarationEntry {
+    member: String,
+    person: Option<String>,
+    board: Option<String>,
+    year: Option<VerksamhetsAr>,
+    reason: String,
+}
+
+impl Declarations {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Kunde inte läsa relationsfilen {}", path))?;
+        Self::parse(&text).with_context(|| format!("Ogiltig relationsfil {}", path))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: DeclarationsFile = toml::from_str(text)?;
+        let mut declarations = Vec::with_capacity(file.declarations.len());
+        for (i, entry) in file.declarations.into_iter().enumerate() {
+            let target = match (entry.person, entry.board, entry.year) {
+                (Some(person), None, None) => DeclaredTarget::Person(person),
+                (None, Some(name), year) => DeclaredTarget::Board { name, year },
+                (Some(_), None, Some(_)) => {
+                    bail!("Deklaration {} har year men ingen board", i + 1)
+                }
+                _ => bail!("Deklaration {} ska ha antingen person eller board", i + 1),
+            };
+            declarations.push(Declaration {
+                member: entry.member,
+                target,
+                reason: entry.reason,
+            });
+        }
+        Ok(Declarations { declarations })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.declarations.is_empty()
+    }
+
+    /// Deklarationer som träffar `board`, i filens ordning. Ledamoten
+    /// identifieras som i det röstande organet `voting_body`.
+    pub fn conflicts_on<'d>(
+        &'d self,
+        board: &Board,
+        voting_body: &str,
+        directory: &Directory,
+    ) -> Vec<DeclaredConflict<'d>> {
+        let mut found = Vec::new();
+        for declaration in &self.declarations {
+            let member = directory.resolve(&declaration.member, voting_body);
+            match &declaration.target {
+                DeclaredTarget::Person(person) => {
+                    let id = directory.resolve(person, &board.name);
+                    for m in board.people() {
+                        if directory.resolve(&m.name, &board.name) == id {
+                            found.push(DeclaredConflict {
+                                member: member.clone(),
+                                declaration,
+                                related: Some((m.name.clone(), m.position.clone())),
+                            });
+                        }
+                    }
+                }
+                DeclaredTarget::Board { name, year } => {
+                    let same_name = directory.matcher.normalize(name)
+                        == directory.matcher.normalize(&board.name);
+                    if same_name && year.is_none_or(|y| y == board.year) {
+                        found.push(DeclaredConflict {
+                            member,
+                            declaration,
+                            related: None,
+                        });
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Ledamöter i deklarationerna som inte finns i `voting_body`.
+    pub fn unknown_members(&self, voting_body: &Board, directory: &Directory) -> Vec<String> {
+        let roster: Vec<PersonId> = voting_body
+            .people()
+            .map(|m| directory.resolve(&m.name, &voting_body.name))
+            .collect();
+        let mut unknown: Vec<String> = Vec::new();
+        for d in &self.declarations {
+            let id = directory.resolve(&d.member, &voting_body.name);
+            if !roster.contains(&id) && !unknown.contains(&d.member) {
+                unknown.push(d.member.clone());
+            }
+        }
+        unknown
+    }
+}
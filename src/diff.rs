@@ -0,0 +1,111 @@
+//! Skillnader mellan två rostrar, t.ex. webbsidan före och efter ett fyllnadsval.
+
+use crate::identity::{Directory, PersonId};
+use crate::types::{Board, Member};
+use crate::year::VerksamhetsAr;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RosterDiff {
+    /// Styrelser (namn, år) som bara finns i den nya rostern.
+    pub added_boards: Vec<(String, VerksamhetsAr)>,
+    /// Styrelser (namn, år) som bara finns i den gamla rostern.
+    pub removed_boards: Vec<(String, VerksamhetsAr)>,
+    /// Styrelser som finns i båda men har ändrats.
+    pub changed: Vec<BoardDiff>,
+}
+
+impl RosterDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_boards.is_empty() && self.removed_boards.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoardDiff {
+    pub name: String,
+    pub year: VerksamhetsAr,
+    pub added: Vec<Member>,
+    pub removed: Vec<Member>,
+    /// Personer som finns kvar men har bytt post: (namn, gammal post, ny post).
+    pub moved: Vec<(String, String, String)>,
+}
+
+/// Jämför styrelser med samma namn och år. Personer jämförs som identiteter,
+/// så att en ny stavning av samma namn inte räknas som en ändring.
+pub fn diff_rosters(old: &[Board], new: &[Board], directory: &Directory) -> RosterDiff {
+    let key = |b: &Board| (b.name.clone(), b.year);
+    let find = |boards: &'_ [Board], b: &Board| {
+        boards
+            .iter()
+            .position(|o| o.name == b.name && o.year == b.year)
+    };
+
+    let mut diff = RosterDiff::default();
+    for board in new {
+        match find(old, board) {
+            None => diff.added_boards.push(key(board)),
+            Some(i) => {
+                let changes = diff_board(&old[i], board, directory);
+                if !(changes.added.is_empty()
+                    && changes.removed.is_empty()
+                    && changes.moved.is_empty())
+                {
+                    diff.changed.push(changes);
+                }
+            }
+        }
+    }
+    for board in old {
+        if find(new, board).is_none() {
+            diff.removed_boards.push(key(board));
+        }
+    }
+    diff.added_boards.sort();
+    diff.removed_boards.sort();
+    diff.changed
+        .sort_by(|a, b| a.year.cmp(&b.year).then_with(|| a.name.cmp(&b.name)));
+    diff
+}
+
+fn diff_board(old: &Board, new: &Board, directory: &Directory) -> BoardDiff {
+    let ids = |board: &Board| -> Vec<(PersonId, Member)> {
+        board
+            .people()
+            .map(|m| (directory.resolve(&m.name, &board.name), m.clone()))
+            .collect()
+    };
+    let old_people = ids(old);
+    let new_people = ids(new);
+    let lookup = |people: &[(PersonId, Member)], id: &PersonId| {
+        people
+            .iter()
+            .find(|(other, _)| other == id)
+            .map(|(_, m)| m.clone())
+    };
+
+    let mut diff = BoardDiff {
+        name: new.name.clone(),
+        year: new.year,
+        added: Vec::new(),
+        removed: Vec::new(),
+        moved: Vec::new(),
+    };
+    for (id, member) in &new_people {
+        match lookup(&old_people, id) {
+            None => diff.added.push(member.clone()),
+            Some(before) if before.position != member.position => diff.moved.push((
+                member.name.clone(),
+                before.position,
+                member.position.clone(),
+            )),
+            Some(_) => {}
+        }
+    }
+    for (id, member) in &old_people {
+        if lookup(&new_people, id).is_none() {
+            diff.removed.push(member.clone());
+        }
+    }
+    diff
+}
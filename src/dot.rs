@@ -0,0 +1,151 @@
+//! Jävsgrafen i Graphviz DOT-format.
+//!
+//! Grafen är bipartit: personer i det röstande organet till vänster och
+//! granskade styrelser till höger, med en kant för varje jäv. Styrelserna
+//! färgas efter röstningsgrupp, styrelser som inte kan behandlas blir röda
+//! och frånvarande personer streckade. Personer med många jäv ritas tjockare.
+//!
+//! ```text
+//! containtment_check --format dot | dot -Tsvg > jav.svg
+//! ```
+
+use crate::analyzer::ConflictReason;
+use crate::policy::Verdict;
+use crate::report::{BoardReport, Report};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Färger för röstningsgrupperna, återanvänds när grupperna är fler.
+const GROUP_COLOURS: [&str; 8] = [
+    "#8dd3c7", "#ffffb3", "#bebada", "#80b1d3", "#fdb462", "#b3de69", "#fccde5", "#d9d9d9",
+];
+const IMPOSSIBLE_COLOUR: &str = "#fb8072";
+
+pub fn render_dot(report: &Report) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "graph jav {{");
+    let _ = writeln!(out, "  rankdir=LR;");
+    let _ = writeln!(
+        out,
+        "  label={};",
+        quote(&format!(
+            "{} ({}): jäv vid ansvarsfrihet",
+            report.voting_body.name, report.voting_body.year
+        ))
+    );
+    let _ = writeln!(out, "  node [fontname=\"Helvetica\"];");
+
+    // Personer samlas från alla styrelser så att antalet jäv blir rätt.
+    let mut people: BTreeMap<&str, (&str, bool, usize)> = BTreeMap::new();
+    let boards: Vec<(&BoardReport, &str)> = report
+        .groups
+        .iter()
+        .enumerate()
+        .flat_map(|(i, group)| {
+            let colour = GROUP_COLOURS[i % GROUP_COLOURS.len()];
+            group.boards.iter().map(move |b| (b, colour))
+        })
+        .chain(report.impossible.iter().map(|b| (b, IMPOSSIBLE_COLOUR)))
+        .collect();
+    for (board, _) in &boards {
+        for p in &board.conflicts {
+            people
+                .entry(p.id.as_str())
+                .or_insert((p.name.as_str(), p.present, 0))
+                .2 += 1;
+        }
+    }
+
+    let _ = writeln!(out, "\n  subgraph personer {{");
+    let _ = writeln!(out, "    rank=same; node [shape=ellipse];");
+    for (id, (name, present, count)) in &people {
+        let style = if *present { "solid" } else { "dashed" };
+        let _ = writeln!(
+            out,
+            "    {} [label={}, style={}, penwidth={}];",
+            quote(&person_node(id)),
+            quote(&format!("{} ({})", name, count)),
+            style,
+            count
+        );
+    }
+    let _ = writeln!(out, "  }}");
+
+    let _ = writeln!(out, "\n  subgraph styrelser {{");
+    let _ = writeln!(out, "    rank=same; node [shape=box, style=filled];");
+    for (i, group) in report.groups.iter().enumerate() {
+        for b in &group.boards {
+            let _ = writeln!(
+                out,
+                "    {} [label={}, fillcolor={}];",
+                quote(&board_node(b)),
+                quote(&format!("{}\n{}\nGrupp {}", b.name, b.year, i + 1)),
+                quote(GROUP_COLOURS[i % GROUP_COLOURS.len()])
+            );
+        }
+    }
+    for b in &report.impossible {
+        let _ = writeln!(
+            out,
+            "    {} [label={}, fillcolor={}];",
+            quote(&board_node(b)),
+            quote(&format!("{}\n{}\nKan ej behandlas", b.name, b.year)),
+            quote(IMPOSSIBLE_COLOUR)
+        );
+    }
+    let _ = writeln!(out, "  }}\n");
+
+    // Jäv som bara kommer från en deklarerad relation ritas streckat med
+    // orsaken som etikett.
+    for (board, colour) in &boards {
+        for p in &board.conflicts {
+            let mut causes = board
+                .causes
+                .iter()
+                .filter(|c| c.person.id == p.id && c.verdict == Verdict::Conflict);
+            let declared = causes
+                .clone()
+                .all(|c| matches!(c.reason, ConflictReason::Declared { .. }));
+            match causes.next() {
+                Some(cause) if declared => {
+                    let _ = writeln!(
+                        out,
+                        "  {} -- {} [color={}, style=dashed, label={}];",
+                        quote(&person_node(p.id.as_str())),
+                        quote(&board_node(board)),
+                        quote(colour),
+                        quote(&cause.reason.describe())
+                    );
+                }
+                _ => {
+                    let _ = writeln!(
+                        out,
+                        "  {} -- {} [color={}];",
+                        quote(&person_node(p.id.as_str())),
+                        quote(&board_node(board)),
+                        quote(colour)
+                    );
+                }
+            }
+        }
+    }
+    let _ = writeln!(out, "}}");
+    out
+}
+
+fn person_node(id: &str) -> String {
+    format!("person:{}", id)
+}
+
+fn board_node(board: &BoardReport) -> String {
+    format!("styrelse:{}:{}", board.name, board.year)
+}
+
+/// En DOT-sträng inom citattecken.
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
@@ -0,0 +1,83 @@
+//! Fel som avbryter en körning, med en egen slutkod per felslag.
+//!
+//! Slutkoderna är stabila så att skript kan skilja felen åt:
+//!
+//! | Kod | Betydelse                                                    |
+//! |-----|--------------------------------------------------------------|
+//! | 0   | Allt gick bra                                                |
+//! | 1   | Utdata kunde inte skrivas                                    |
+//! | 2   | Felaktiga argument (från `clap`)                             |
+//! | 3   | Källan kunde inte hämtas (nätverk eller fil)                 |
+//! | 4   | Källan kunde inte parsas                                     |
+//! | 5   | Ogiltig konfigurations-, alias-, mall- eller korrigeringsfil |
+//! | 6   | Det röstande organet finns inte i källan                     |
+//! | 7   | Verksamhetsåret är okänt eller har fel format                |
+//! | 8   | Inga styrelser finns för det granskade året                  |
+//! | 10  | Mötet är inte beslutsmässigt                                 |
+
+use crate::year::VerksamhetsAr;
+use std::io;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Kunde inte läsa {location}: {reason}")]
+    Fetch { location: String, reason: String },
+
+    #[error("Kunde inte parsa {location}: {reason}")]
+    Parse { location: String, reason: String },
+
+    #[error("{0}")]
+    Config(String),
+
+    #[error("Kunde inte hitta det röstande organet '{name}'{}", year_suffix(.year))]
+    VotingBodyNotFound {
+        name: String,
+        year: Option<VerksamhetsAr>,
+    },
+
+    #[error("Verksamhetsåret före '{0}' går inte att räkna ut")]
+    InvalidYear(String),
+
+    #[error("Hittade inga styrelser för året {0}")]
+    NoBoardsForYear(VerksamhetsAr),
+
+    #[error("Mötet är inte beslutsmässigt: {present} närvarande, {required} krävs")]
+    NotQuorate { present: usize, required: usize },
+
+    #[error("Kunde inte skriva utdata: {0}")]
+    Output(String),
+}
+
+impl Error {
+    /// Processens slutkod för felet, se tabellen i modulens dokumentation.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Output(_) => 1,
+            Error::Fetch { .. } => 3,
+            Error::Parse { .. } => 4,
+            Error::Config(_) => 5,
+            Error::VotingBodyNotFound { .. } => 6,
+            Error::InvalidYear(_) => 7,
+            Error::NoBoardsForYear(_) => 8,
+            Error::NotQuorate { .. } => 10,
+        }
+    }
+
+    /// Ett fel från en inläst inställningsfil, med hela orsakskedjan.
+    pub fn config(err: anyhow::Error) -> Self {
+        Error::Config(format!("{:#}", err))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Output(err.to_string())
+    }
+}
+
+fn year_suffix(year: &Option<VerksamhetsAr>) -> String {
+    year.as_ref()
+        .map(|y| format!(" för {}", y))
+        .unwrap_or_default()
+}
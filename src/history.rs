@@ -0,0 +1,104 @@
+//! Vilka poster varje person har haft, över alla styrelser och år.
+
+use crate::identity::{Directory, PersonId};
+use crate::types::Board;
+use crate::year::VerksamhetsAr;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Role {
+    /// Namnet som det är skrivet i styrelsens roster.
+    pub name: String,
+    pub board: String,
+    pub year: VerksamhetsAr,
+    pub position: String,
+    pub deputy: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PersonHistory {
+    pub id: PersonId,
+    /// Namnet från aliasfilen, annars den första stavningen i rostern.
+    pub name: String,
+    /// Posterna sorterade efter år och styrelse.
+    pub roles: Vec<Role>,
+}
+
+/// Alla personer i styrelserna, sorterade efter namn. Vakanta poster räknas inte.
+pub fn people_history(boards: &[Board], directory: &Directory) -> Vec<PersonHistory> {
+    let mut by_id: HashMap<PersonId, PersonHistory> = HashMap::new();
+    for board in boards {
+        for member in board.people() {
+            let id = directory.resolve(&member.name, &board.name);
+            let entry = by_id.entry(id.clone()).or_insert_with(|| PersonHistory {
+                name: directory
+                    .display_name(&id)
+                    .unwrap_or(&member.name)
+                    .to_string(),
+                id,
+                roles: Vec::new(),
+            });
+            entry.roles.push(Role {
+                name: member.name.clone(),
+                board: board.name.clone(),
+                year: board.year,
+                position: member.position.clone(),
+                deputy: member.is_deputy(),
+            });
+        }
+    }
+
+    let mut people: Vec<PersonHistory> = by_id.into_values().collect();
+    for person in &mut people {
+        person
+            .roles
+            .sort_by(|a, b| a.year.cmp(&b.year).then_with(|| a.board.cmp(&b.board)));
+    }
+    people.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    people
+}
+
+/// Personer som matchar `query`, med avståndet till närmaste stavning.
+///
+/// Finns personen i aliasfilen eller under exakt det namnet (enligt
+/// `NameMatcher`) returneras bara de träffarna med avstånd 0. Annars
+/// returneras alla vars namn ligger inom `threshold`, närmast först.
+pub fn find_people<'a>(
+    people: &'a [PersonHistory],
+    query: &str,
+    directory: &Directory,
+    threshold: usize,
+) -> Vec<(&'a PersonHistory, usize)> {
+    let matcher = &directory.matcher;
+    let aliased: Vec<&PersonId> = directory
+        .people
+        .iter()
+        .filter(|p| {
+            matcher.same(&p.name, query) || p.aliases.iter().any(|a| matcher.same(a, query))
+        })
+        .map(|p| &p.id)
+        .collect();
+
+    let spellings = |p: &'a PersonHistory| {
+        std::iter::once(p.name.as_str()).chain(p.roles.iter().map(|r| r.name.as_str()))
+    };
+    let exact: Vec<(&PersonHistory, usize)> = people
+        .iter()
+        .filter(|p| aliased.contains(&&p.id) || spellings(p).any(|n| matcher.same(n, query)))
+        .map(|p| (p, 0))
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    let mut fuzzy: Vec<(&PersonHistory, usize)> = people
+        .iter()
+        .filter_map(|p| {
+            let distance = spellings(p).map(|n| matcher.distance(n, query)).min()?;
+            (distance <= threshold).then_some((p, distance))
+        })
+        .collect();
+    fuzzy.sort_by_key(|(_, distance)| *distance);
+    fuzzy
+}
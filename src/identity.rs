@@ -0,0 +1,152 @@
+//! Stabila identiteter för personer oberoende av hur namnen stavas.
+//!
+//! Aliasfilen (TOML) kopplar stavningsvarianter, smeknamn och nya efternamn
+//! till samma `PersonId`, och kan skilja på två personer med samma namn
+//! genom att begränsa en post till vissa styrelser:
+//!
+//! ```toml
+//! [[person]]
+//! id = "erik-nilsson-fum"
+//! name = "Erik Nilsson"
+//! aliases = ["Eric Nilsson"]
+//! boards = ["FUM", "KIDS"]
+//! ```
+
+use crate::names::NameMatcher;
+use crate::types::{Board, Member};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PersonId(String);
+
+impl PersonId {
+    pub fn new(id: impl Into<String>) -> Self {
+        PersonId(id.into())
+    }
+
+    /// Identitet för ett namn som inte finns i aliasfilen.
+    pub fn from_name(name: &str) -> Self {
+        Self::from_name_with(name, &NameMatcher::default())
+    }
+
+    pub fn from_name_with(name: &str, matcher: &NameMatcher) -> Self {
+        PersonId(matcher.key(name))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PersonId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonEntry {
+    pub id: PersonId,
+    /// Namnet som visas i rapporterna.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Om angivet gäller posten bara på dessa styrelser.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub boards: Vec<String>,
+}
+
+impl PersonEntry {
+    fn matches_name(&self, name: &str, matcher: &NameMatcher) -> bool {
+        matcher.same(&self.name, name) || self.aliases.iter().any(|a| matcher.same(a, name))
+    }
+
+    fn applies_to(&self, board: &str) -> bool {
+        self.boards
+            .iter()
+            .any(|b| NameMatcher::default().same(b, board))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Directory {
+    #[serde(default, rename = "person")]
+    pub people: Vec<PersonEntry>,
+    /// Hur namn jämförs mot posterna, sätts av anroparen.
+    #[serde(skip)]
+    pub matcher: NameMatcher,
+}
+
+impl Directory {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Kunde inte läsa aliasfilen {}", path))?;
+        Self::parse(&text).with_context(|| format!("Ogiltig aliasfil {}", path))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("Kunde inte skriva aliasfilen {}", path))
+    }
+
+    /// Posten som gäller för `name` på styrelsen `board`, om någon.
+    ///
+    /// Poster som är begränsade till styrelsen går före generella poster.
+    pub fn lookup(&self, name: &str, board: &str) -> Option<&PersonEntry> {
+        let mut candidates = self
+            .people
+            .iter()
+            .filter(|p| p.matches_name(name, &self.matcher));
+        let general = candidates.clone().find(|p| p.boards.is_empty());
+        candidates.find(|p| p.applies_to(board)).or(general)
+    }
+
+    pub fn resolve(&self, name: &str, board: &str) -> PersonId {
+        match self.lookup(name, board) {
+            Some(entry) => entry.id.clone(),
+            None => PersonId::from_name_with(name, &self.matcher),
+        }
+    }
+
+    /// Ordinarie eller ersättare på `board` med samma identitet som `name`,
+    /// så att även alias och andra namnformer i aliasfilen hittas.
+    pub fn find_on<'b>(&self, board: &'b Board, name: &str) -> Option<&'b Member> {
+        let id = self.resolve(name, &board.name);
+        board
+            .people()
+            .find(|m| self.resolve(&m.name, &board.name) == id)
+    }
+
+    /// Visningsnamn för en identitet som finns i aliasfilen.
+    pub fn display_name(&self, id: &PersonId) -> Option<&str> {
+        self.people
+            .iter()
+            .find(|p| &p.id == id)
+            .map(|p| p.name.as_str())
+    }
+
+    /// Lägger till `alias` för identiteten `id`, och skapar posten vid behov.
+    pub fn add_alias(&mut self, id: &PersonId, name: &str, alias: &str) {
+        match self.people.iter_mut().find(|p| &p.id == id) {
+            Some(entry) => {
+                if !entry.matches_name(alias, &self.matcher) {
+                    entry.aliases.push(alias.to_string());
+                }
+            }
+            None => self.people.push(PersonEntry {
+                id: id.clone(),
+                name: name.to_string(),
+                aliases: vec![alias.to_string()],
+                boards: Vec::new(),
+            }),
+        }
+    }
+}
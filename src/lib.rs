@@ -1,3 +1,23 @@
 pub mod analyzer;
+pub mod audit;
+pub mod config;
+pub mod corrections;
+pub mod declarations;
+pub mod diff;
+pub mod dot;
+pub mod error;
+pub mod history;
+pub mod identity;
+pub mod meeting;
+pub mod names;
 pub mod parser;
+pub mod partition;
+pub mod policy;
+pub mod protocol;
+pub mod quorum;
+pub mod report;
+pub mod roster;
+pub mod simulation;
+pub mod source;
 pub mod types;
+pub mod year;
@@ -1,26 +1,107 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use colored::*;
-use containtment_check::analyzer::{AnalysisResult, analyze_voting_groups};
-use containtment_check::parser::parse_html_boards;
-use containtment_check::types::{Board, Member};
-use std::collections::HashSet;
+use containtment_check::analyzer::{AnalysisOptions, CallIn, ConflictReason, SeatSummary};
+use containtment_check::audit::{Audit, AuditResult};
+use containtment_check::config::Config;
+use containtment_check::corrections::{Correction, Decision, DecisionLog, Suggestion};
+use containtment_check::declarations::Declarations;
+use containtment_check::diff::diff_rosters;
+use containtment_check::dot::render_dot;
+use containtment_check::error::Error;
+use containtment_check::history::{PersonHistory, find_people, people_history};
+use containtment_check::identity::Directory;
+use containtment_check::meeting::{DecidedGroup, Involvement, Meeting, MeetingCommand};
+use containtment_check::names::NameMatcher;
+use containtment_check::policy::{ConflictPolicy, Verdict};
+use containtment_check::protocol::ProtocolTemplate;
+use containtment_check::quorum::QuorumRule;
+use containtment_check::report::{BoardReport, CallInReport, GroupReport, PersonReport, Report};
+use containtment_check::roster::{RosterFormat, render_roster};
+use containtment_check::simulation::{AttendanceFile, AttendanceSource, Simulation, simulate};
+use containtment_check::source::load_boards;
+use containtment_check::types::Board;
+use containtment_check::year::{VerksamhetsAr, YearRange, join_years};
 use std::fs;
-use tracing::{error, info, warn};
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+use tracing::{Level, error, info, warn};
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-struct Args {
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Utan underkommando körs `audit`.
+    #[command(flatten)]
+    audit: AuditArgs,
+
+    #[command(flatten)]
+    global: GlobalArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Dela in granskade styrelser i röstningsgrupper (standard)
+    Audit(AuditArgs),
+    /// Lista de parsade styrelserna per verksamhetsår
+    Boards(BoardsArgs),
+    /// Lista alla personer och deras poster över åren
+    People(PeopleArgs),
+    /// Jämför styrelserna i två källor
+    Diff(DiffArgs),
+    /// Interaktivt mötesläge där närvaron uppdateras under mötet
+    Meeting(AuditArgs),
+    /// Simulera närvaron före mötet och visa risken per styrelse
+    Simulate(SimulateArgs),
+}
+
+/// Inställningar som gäller alla underkommandon.
+#[derive(Args, Debug)]
+struct GlobalArgs {
+    /// TOML-fil med alias som kopplar namnvarianter till samma person
+    #[arg(long, global = true)]
+    aliases: Option<String>,
+
+    /// Räkna namn som bara skiljer sig i accenter (ö/o, é/e) som samma namn
+    #[arg(long, global = true)]
+    fold_diacritics: bool,
+
+    /// TOML-fil med organisationens inställningar (t.ex. kvorumregel)
+    #[arg(long, global = true)]
+    config: Option<String>,
+
+    /// Detaljnivå för loggen på stderr (error, warn, info, debug, trace).
+    /// Parserns diagnostik om överhoppade flikar och rader syns på debug och trace.
+    #[arg(long, global = true, default_value_t = Level::INFO)]
+    log_level: Level,
+}
+
+#[derive(Args, Debug)]
+struct SourceArgs {
     #[arg(
         short,
         long,
         default_value = "https://bthstudent.se/studentkaren/fortroendevalda/"
     )]
     source: String,
+}
+
+#[derive(Args, Debug)]
+struct AuditArgs {
+    #[command(flatten)]
+    input: SourceArgs,
 
     #[arg(short, long, default_value = "Fullmäktige")]
     base_board: String,
 
     #[arg(short, long)]
-    voting_year: Option<String>,
+    voting_year: Option<VerksamhetsAr>,
+
+    /// Granska dessa år i stället för året före det röstande organets,
+    /// t.ex. `2023/2024,2024/2025` eller `2022/2023..2024/2025`
+    #[arg(long, value_delimiter = ',')]
+    audit_years: Vec<YearRange>,
 
     #[arg(long, value_delimiter = ',')]
     absent: Vec<String>,
@@ -28,121 +109,642 @@ struct Args {
     #[arg(long, default_value_t = 3)]
     le_threshold: usize,
 
+    /// Granska varje namnkorrigering innan den tillämpas
+    #[arg(long)]
+    review: bool,
+
+    /// TOML-fil med tidigare beslut om namnkorrigeringar (uppdateras vid --review)
+    #[arg(long)]
+    corrections_file: Option<String>,
+
     #[arg(long, value_delimiter = ',')]
     priority: Vec<String>,
+
+    /// Kvorumregel: half, more-than-half, fixed:N, A/B eller present:A/B
+    #[arg(long)]
+    quorum: Option<QuorumRule>,
+
+    /// Antal mandat enligt stadgarna (inklusive vakanta)
+    #[arg(long)]
+    seats: Option<usize>,
+
+    /// Maximal söktid i millisekunder för den optimala gruppindelningen
+    #[arg(long, default_value_t = 2000)]
+    solver_timeout_ms: u64,
+
+    /// Föredra grupper med minst så många röstberättigade utöver kvorum
+    #[arg(long)]
+    min_margin: Option<usize>,
+
+    /// TOML-fil som anger vilka poster som ger jäv
+    #[arg(long)]
+    policy: Option<String>,
+
+    /// TOML-fil med deklarerade relationer som ger jäv (partner, anställning)
+    #[arg(long)]
+    declarations: Option<String>,
+
+    /// Spara de parsade styrelserna som .json/.toml och avsluta
+    #[arg(long)]
+    export: Option<String>,
+
+    /// Utdataformat för analysen
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// TOML-fil som ersätter texterna i protokollsutkastet
+    #[arg(long)]
+    template: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct BoardsArgs {
+    #[command(flatten)]
+    input: SourceArgs,
+
+    /// Visa bara detta verksamhetsår
+    #[arg(short, long)]
+    year: Option<VerksamhetsAr>,
+
+    /// Visa ledamöterna i varje styrelse
+    #[arg(short, long)]
+    members: bool,
+}
+
+#[derive(Args, Debug)]
+struct PeopleArgs {
+    #[command(flatten)]
+    input: SourceArgs,
+
+    /// Visa bara personer som matchar namnet (alias och stavfel tillåts)
+    name: Option<String>,
+
+    /// Största redigeringsavstånd för en ungefärlig träff
+    #[arg(long, default_value_t = 3)]
+    le_threshold: usize,
 }
 
-fn main() {
+#[derive(Args, Debug)]
+struct SimulateArgs {
+    #[command(flatten)]
+    audit: AuditArgs,
+
+    /// TOML-fil med närvarosannolikheter och tidigare möten
+    #[arg(long)]
+    attendance: Option<String>,
+
+    /// Antal dragna närvarouppsättningar
+    #[arg(long, default_value_t = 1000)]
+    runs: usize,
+
+    /// Frö för dragningarna, för att kunna upprepa en körning
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// Den äldre källan (fil eller URL)
+    old: String,
+    /// Den nyare källan (fil eller URL)
+    new: String,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Färgad text för terminalen
+    Text,
+    /// Hela analysen som JSON på stdout
+    Json,
+    /// Protokollsutkast i Markdown
+    Markdown,
+    /// Jävsgrafen för Graphviz
+    Dot,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
     tracing_subscriber::fmt()
         .without_time()
         .with_target(false)
+        .with_max_level(cli.global.log_level)
+        .with_writer(io::stderr)
         .init();
 
-    let args = Args::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
 
-    let content = fetch_content(&args.source);
-    info!("Parsar HTML-innehåll...");
+fn run(cli: Cli) -> Result<(), Error> {
+    let config = match &cli.global.config {
+        Some(path) => Config::load(path).map_err(Error::config)?,
+        None => Config::default(),
+    };
 
-    let mut boards = match parse_html_boards(&content) {
-        Ok(b) => {
-            info!("Hittade {} styrelser totalt.", b.len());
-            b
+    let mut directory = match cli.global.aliases.as_ref().or(config.aliases.as_ref()) {
+        Some(path) => {
+            let d = Directory::load(path).map_err(Error::config)?;
+            info!("Läste {} personer från aliasfilen.", d.people.len());
+            d
         }
-        Err(e) => {
-            error!("Kritisk fel vid parsning: {}", e);
-            return;
+        None => Directory::default(),
+    };
+    directory.matcher =
+        NameMatcher::new(cli.global.fold_diacritics || config.fold_diacritics.unwrap_or(false));
+
+    match cli.command.unwrap_or(Command::Audit(cli.audit)) {
+        Command::Audit(args) => run_audit(&args, &config, directory),
+        Command::Boards(args) => run_boards(&args),
+        Command::People(args) => run_people(&args, &directory),
+        Command::Diff(args) => run_diff(&args, &directory),
+        Command::Meeting(args) => run_meeting(&args, &config, directory),
+        Command::Simulate(args) => run_simulate(&args, &config, directory),
+    }
+}
+
+fn run_audit(args: &AuditArgs, config: &Config, directory: Directory) -> Result<(), Error> {
+    let template = match args.template.as_ref().or(config.template.as_ref()) {
+        Some(path) => ProtocolTemplate::load(path).map_err(Error::config)?,
+        None => ProtocolTemplate::default(),
+    };
+
+    let boards = load_boards(&args.input.source)?;
+
+    if let Some(path) = &args.export {
+        return export_boards(&boards, path);
+    }
+
+    let options = analysis_options(args, config, &directory)?;
+    let (result, from_file) = run_pipeline(args, boards, options, &directory)?;
+
+    let report = &result.report;
+    match args.format {
+        OutputFormat::Text => {
+            print_absences(&result.absent);
+            print_fum_info(&result.voting_body, &result.absent);
+            println!(
+                "\n{}",
+                format!(
+                    "{}: {}",
+                    if result.audited_years.len() > 1 {
+                        "GRANSKAR VERKSAMHETSÅREN"
+                    } else {
+                        "GRANSKAR VERKSAMHETSÅRET"
+                    },
+                    join_years(&result.audited_years)
+                )
+                .bold()
+                .underline()
+            );
+            println!("------------------------------------------------");
+            print_corrections(&result.suggestions, &result.corrections, &from_file);
+            print_priority(&args.priority);
+            print_analysis_results(report);
+        }
+        OutputFormat::Json => {
+            let json = report.to_json().map_err(|e| Error::Output(e.to_string()))?;
+            println!("{}", json);
         }
+        OutputFormat::Markdown => print!("{}", template.render(report)),
+        OutputFormat::Dot => print!("{}", render_dot(report)),
+    }
+
+    if !report.quorate {
+        return Err(Error::NotQuorate {
+            present: report.seated,
+            required: report.quorum_limit,
+        });
+    }
+    Ok(())
+}
+
+fn analysis_options(
+    args: &AuditArgs,
+    config: &Config,
+    directory: &Directory,
+) -> Result<AnalysisOptions, Error> {
+    let policy = match args.policy.as_ref().or(config.policy.as_ref()) {
+        Some(path) => ConflictPolicy::load(path).map_err(Error::config)?,
+        None => ConflictPolicy::default(),
     };
+    let declarations = match args.declarations.as_ref().or(config.declarations.as_ref()) {
+        Some(path) => Declarations::load(path).map_err(Error::config)?,
+        None => Declarations::default(),
+    };
+    Ok(AnalysisOptions {
+        quorum_rule: args.quorum.or(config.quorum).unwrap_or_default(),
+        seats: args.seats.or(config.seats),
+        directory: directory.clone(),
+        time_budget: Duration::from_millis(args.solver_timeout_ms),
+        min_margin: args.min_margin.or(config.min_margin).unwrap_or(0),
+        policy,
+        declarations,
+    })
+}
 
-    let (fum_name, fum_year, fum_member_names) = {
-        let candidates: Vec<&Board> = boards
-            .iter()
-            .filter(|b| b.name == args.base_board)
-            .collect();
+/// Kör granskningen med namnkorrigeringar enligt korrigeringsfilen och
+/// `--review`. Returnerar även förslagen som avgjordes av korrigeringsfilen.
+fn run_pipeline(
+    args: &AuditArgs,
+    boards: Vec<Board>,
+    options: AnalysisOptions,
+    directory: &Directory,
+) -> Result<(AuditResult, Vec<Suggestion>), Error> {
+    let mut log = match &args.corrections_file {
+        Some(path) if fs::metadata(path).is_ok() => {
+            DecisionLog::load(path).map_err(Error::config)?
+        }
+        _ => DecisionLog::default(),
+    };
+    let mut from_file: Vec<Suggestion> = Vec::new();
+    let declarations = options.declarations.clone();
 
-        let fum_opt = if let Some(y) = &args.voting_year {
-            candidates.into_iter().find(|b| &b.year == y)
-        } else {
-            candidates.into_iter().max_by_key(|b| b.year.clone())
-        };
+    let mut audit = Audit::new(boards)
+        .base_board(&args.base_board)
+        .absent(&args.absent)
+        .priority(&args.priority)
+        .le_threshold(args.le_threshold)
+        .options(options);
+    if let Some(year) = &args.voting_year {
+        audit = audit.voting_year(*year);
+    }
+    if !args.audit_years.is_empty() {
+        audit = audit.audit_years(args.audit_years.iter().flat_map(YearRange::years));
+    }
+    // Beslut i korrigeringsfilen gäller i första hand. Övriga förslag granskas
+    // interaktivt med `--review`, annars godkänns det närmaste namnet.
+    let result = audit
+        .decide_corrections(|suggestion| {
+            if let Some(decision) = log.find(suggestion, directory) {
+                from_file.push(suggestion.clone());
+                return Ok(decision.replace_with.clone());
+            }
+            if !args.review {
+                return Ok(Some(suggestion.candidates[0].name.clone()));
+            }
+            let choice = prompt_decision(suggestion)?;
+            log.record(
+                Decision {
+                    board: suggestion.board.clone(),
+                    year: suggestion.year,
+                    found: suggestion.found.clone(),
+                    replace_with: choice.clone(),
+                },
+                directory,
+            );
+            Ok(choice)
+        })
+        .run()?;
 
-        match fum_opt {
-            Some(b) => (
-                b.name.clone(),
-                b.year.clone(),
-                b.members.iter().map(|m| m.name.clone()).collect::<Vec<_>>(),
-            ),
-            None => {
-                error!("Kunde inte hitta huvudstyrelsen '{}'.", args.base_board);
-                return;
+    if args.review
+        && let Some(path) = &args.corrections_file
+    {
+        log.save(path)
+            .map_err(|e| Error::Output(format!("{:#}", e)))?;
+        info!("Sparade besluten i {}.", path);
+    }
+    for name in declarations.unknown_members(&result.voting_body, directory) {
+        warn!(
+            "Kunde inte hitta '{}' från relationsfilen i FUM-listan. Kontrollera stavning!",
+            name
+        );
+    }
+    for name in &result.unknown_absent {
+        warn!(
+            "Kunde inte hitta '{}' i FUM-listan. Kontrollera stavning!",
+            name
+        );
+    }
+    Ok((result, from_file))
+}
+
+/// Mötesläget läser kommandon från stdin tills `quit` eller filslut.
+fn run_meeting(args: &AuditArgs, config: &Config, directory: Directory) -> Result<(), Error> {
+    let boards = load_boards(&args.input.source)?;
+    let options = analysis_options(args, config, &directory)?;
+    let (result, _) = run_pipeline(args, boards, options.clone(), &directory)?;
+    let mut meeting = Meeting::new(&result, options);
+
+    print_fum_info(&result.voting_body, meeting.absent());
+    println!();
+    print_analysis_results(meeting.plan());
+    print_meeting_help();
+
+    let stdin = io::stdin();
+    loop {
+        eprint!("möte> ");
+        io::stderr().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match line.parse::<MeetingCommand>() {
+            Ok(command) => command,
+            Err(e) => {
+                println!("{}", e.red());
+                continue;
             }
+        };
+        let outcome = match command {
+            MeetingCommand::Quit => break,
+            MeetingCommand::Help => {
+                print_meeting_help();
+                continue;
+            }
+            MeetingCommand::Plan => Ok(String::new()),
+            MeetingCommand::Arrive(name) => {
+                meeting.arrive(&name).map(|n| format!("{} har kommit.", n))
+            }
+            MeetingCommand::Leave(name) => meeting.leave(&name).map(|n| format!("{} har gått.", n)),
+            MeetingCommand::Done(number) => meeting
+                .done(number)
+                .map(|d| format!("Grupp {} är beslutad (beslut {}).", number, d.number)),
+        };
+        match outcome {
+            Ok(message) => {
+                if !message.is_empty() {
+                    println!("{}\n", message.green().bold());
+                }
+                print_decided(meeting.decided());
+                if meeting.is_finished() {
+                    println!("{}", "Alla styrelser är behandlade.".green().bold());
+                    break;
+                }
+                print_analysis_results(meeting.plan());
+            }
+            Err(e) => println!("{}", e.red()),
         }
+    }
+    Ok(())
+}
+
+fn run_simulate(args: &SimulateArgs, config: &Config, directory: Directory) -> Result<(), Error> {
+    let attendance = match args.attendance.as_ref().or(config.attendance.as_ref()) {
+        Some(path) => AttendanceFile::load(path).map_err(Error::config)?,
+        None => AttendanceFile::default(),
     };
+    let boards = load_boards(&args.audit.input.source)?;
+    let options = analysis_options(&args.audit, config, &directory)?;
+    let (result, _) = run_pipeline(&args.audit, boards, options.clone(), &directory)?;
 
-    let absent_set = process_absences(&fum_member_names, &args.absent);
-    print_fum_info(&fum_name, &fum_year, &fum_member_names, &absent_set);
+    let (people, unknown) = attendance.resolve(&result.voting_body, &result.absent, &directory);
+    for name in &unknown {
+        warn!(
+            "Kunde inte hitta '{}' från närvarofilen i FUM-listan. Kontrollera stavning!",
+            name
+        );
+    }
+    let seed = args.seed.unwrap_or_else(|| fastrand::u64(..));
+    let boards: Vec<&Board> = result.boards.iter().collect();
+    let simulation = simulate(
+        &result.voting_body,
+        &boards,
+        &people,
+        args.runs,
+        seed,
+        &options,
+    );
 
-    let target_year = match get_previous_year(&fum_year) {
-        Ok(y) => y,
-        Err(e) => {
-            error!("Fel vid årsberäkning: {}", e);
-            return;
+    match args.audit.format {
+        OutputFormat::Text => print_simulation(&simulation),
+        OutputFormat::Json => {
+            let json = simulation
+                .to_json()
+                .map_err(|e| Error::Output(e.to_string()))?;
+            println!("{}", json);
         }
-    };
+        format => {
+            return Err(Error::Config(format!(
+                "Simuleringen kan inte skrivas som {:?}, använd text eller json",
+                format
+            )));
+        }
+    }
+    Ok(())
+}
 
+fn print_simulation(simulation: &Simulation) {
+    let percent = |share: f64| format!("{:5.1} %", share * 100.0);
     println!(
-        "\n{}",
-        format!("GRANSKAR VERKSAMHETSÅRET: {}", target_year)
-            .bold()
-            .underline()
+        "{}",
+        format!(
+            "SIMULERAD NÄRVARO ({} körningar, frö {})",
+            simulation.runs, simulation.seed
+        )
+        .bold()
+        .underline()
     );
-    println!("------------------------------------------------");
-
-    apply_fuzzy_corrections(
-        &mut boards,
-        &fum_member_names,
-        &target_year,
-        args.le_threshold,
+    println!("  Kvorumregel: {}", simulation.quorum_description);
+    println!("  Närvarosannolikheter:");
+    for a in &simulation.attendance {
+        let source = match a.source {
+            AttendanceSource::Given => "angiven".to_string(),
+            AttendanceSource::PastMeetings { attended, listed } => {
+                format!("{} av {} möten", attended, listed)
+            }
+            AttendanceSource::Absent => "anmäld frånvaro".to_string(),
+            AttendanceSource::Default => "standard".to_string(),
+        };
+        println!(
+            "    {:<30} {} ({})",
+            a.name,
+            percent(a.probability),
+            source.dimmed()
+        );
+    }
+    println!();
+    println!(
+        "  Beslutsmässigt:                {}",
+        percent(simulation.quorate)
     );
+    println!(
+        "  Alla styrelser kan behandlas:  {}",
+        percent(simulation.all_decidable)
+    );
+    match simulation.expected_groups {
+        Some(groups) => println!("  Förväntat antal grupper:       {:5.1}", groups),
+        None => println!("  Förväntat antal grupper:       {}", "-".dimmed()),
+    }
+    println!();
+    println!("  Risk att styrelsen inte kan behandlas:");
+    for b in &simulation.boards {
+        let line = format!(
+            "    {:<30} {}",
+            format!("{} ({})", b.name, b.year),
+            percent(b.impossible)
+        );
+        if b.impossible >= 0.5 {
+            println!("{}", line.red());
+        } else if b.impossible > 0.0 {
+            println!("{}", line.yellow());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
 
-    let fum_ref = boards
-        .iter()
-        .find(|b| b.name == fum_name && b.year == fum_year)
-        .expect("FUM borde finnas kvar");
-
-    let mut boards_to_audit: Vec<_> = boards.iter().filter(|b| b.year == target_year).collect();
+fn print_meeting_help() {
+    println!("{}", "MÖTESLÄGE".blue().bold());
+    println!("  arrive <namn>    personen har kommit");
+    println!("  leave <namn>     personen har gått");
+    println!("  done group <n>   grupp n i planen är beslutad");
+    println!("  plan             visa planen igen");
+    println!("  quit             avsluta");
+    println!();
+}
 
-    if boards_to_audit.is_empty() {
-        warn!("Hittade inga styrelser för året {}.", target_year);
+/// Beslutade grupper ligger fast. De som den senaste närvaroändringen berör
+/// visas med vem det gällde, så att mötet kan ta ställning till dem.
+fn print_decided(decided: &[DecidedGroup]) {
+    if decided.is_empty() {
         return;
     }
-
-    if !args.priority.is_empty() {
-        let priority_set: HashSet<String> = args
-            .priority
+    println!("{}", "REDAN BESLUTADE".blue().bold());
+    for d in decided {
+        let boards: Vec<String> = d
+            .group
+            .boards
             .iter()
-            .map(|s| s.trim().to_lowercase())
+            .map(|b| format!("{} ({})", b.name, b.year))
             .collect();
+        println!("  Beslut {}: {}", d.number, boards.join(", ").dimmed());
+        match &d.touched {
+            Some(Involvement::Voter(name)) => println!(
+                "    {}",
+                format!("Berörs av ändringen: {} röstade i beslutet", name).yellow()
+            ),
+            Some(Involvement::Conflicted(name)) => println!(
+                "    {}",
+                format!("Berörs av ändringen: {} är jävig i beslutet", name).yellow()
+            ),
+            None => {}
+        }
+    }
+    println!("------------------------------------------------\n");
+}
 
-        boards_to_audit.sort_by_key(|b| !priority_set.contains(&b.name.to_lowercase()));
+fn run_boards(args: &BoardsArgs) -> Result<(), Error> {
+    let mut boards = load_boards(&args.input.source)?;
+    boards.sort_by(|a, b| b.year.cmp(&a.year).then_with(|| a.name.cmp(&b.name)));
 
-        println!("{}", "PRIORITERING AKTIVERAD".blue().bold());
-        println!("Följande styrelser behandlas först:");
-        for p in &args.priority {
-            println!("  -> {}", p.yellow());
+    let mut current_year: Option<VerksamhetsAr> = None;
+    for board in &boards {
+        if args.year.is_some_and(|y| y != board.year) {
+            continue;
         }
-        println!("------------------------------------------------\n");
+        if current_year != Some(board.year) {
+            println!("\n{}", board.year.to_string().bold().underline());
+            current_year = Some(board.year);
+        }
+        let vacant = board.members.iter().filter(|m| m.is_vacant()).count();
+        let mut summary = format!("{} ledamöter", board.seated_members().count());
+        if board.seated_deputies().count() > 0 {
+            summary += &format!(", {} ersättare", board.seated_deputies().count());
+        }
+        if vacant > 0 {
+            summary += &format!(", {} vakanta", vacant);
+        }
+        println!("  {} ({})", board.name.cyan(), summary);
+        if args.members {
+            for m in board.members.iter().chain(&board.deputies) {
+                println!("    - {}: {}", m.position.dimmed(), m.name);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_people(args: &PeopleArgs, directory: &Directory) -> Result<(), Error> {
+    let boards = load_boards(&args.input.source)?;
+    let people = people_history(&boards, directory);
+
+    let Some(query) = &args.name else {
+        for person in &people {
+            print_person(person);
+        }
+        return Ok(());
+    };
+    let matches = find_people(&people, query, directory, args.le_threshold);
+    if matches.is_empty() {
+        warn!("Hittade ingen person som liknar '{}'.", query);
+        return Ok(());
+    }
+    for (person, distance) in matches {
+        if distance > 0 {
+            println!(
+                "{}",
+                format!("(Möjlig träff, avstånd {} tecken)", distance).yellow()
+            );
+        }
+        print_person(person);
+    }
+    Ok(())
+}
+
+fn print_person(person: &PersonHistory) {
+    println!("{}", person.name.bold());
+    for role in &person.roles {
+        let spelling = if role.name == person.name {
+            String::new()
+        } else {
+            format!(" (som '{}')", role.name)
+        };
+        println!(
+            "  {} {}: {}{}",
+            role.year.to_string().dimmed(),
+            role.board.cyan(),
+            role.position,
+            spelling.dimmed()
+        );
     }
+}
 
-    let analysis = analyze_voting_groups(fum_ref, &boards_to_audit, &absent_set);
-    print_analysis_results(&analysis, &absent_set);
+fn run_diff(args: &DiffArgs, directory: &Directory) -> Result<(), Error> {
+    let old = load_boards(&args.old)?;
+    let new = load_boards(&args.new)?;
+    let diff = diff_rosters(&old, &new, directory);
+    if diff.is_empty() {
+        println!("{}", "[OK] Inga skillnader.".green());
+        return Ok(());
+    }
+    for (name, year) in &diff.added_boards {
+        println!("{} {} ({})", "+".green().bold(), name.green(), year);
+    }
+    for (name, year) in &diff.removed_boards {
+        println!("{} {} ({})", "-".red().bold(), name.red(), year);
+    }
+    for board in &diff.changed {
+        println!(
+            "{} {} ({})",
+            "~".yellow().bold(),
+            board.name.yellow(),
+            board.year
+        );
+        for m in &board.added {
+            println!("    {} {}: {}", "+".green(), m.position, m.name);
+        }
+        for m in &board.removed {
+            println!("    {} {}: {}", "-".red(), m.position, m.name);
+        }
+        for (name, before, after) in &board.moved {
+            println!("    {} {}: {} -> {}", "~".yellow(), name, before, after);
+        }
+    }
+    Ok(())
 }
 
-fn apply_fuzzy_corrections(
-    boards: &mut [Board],
-    correct_names: &[String],
-    target_year: &str,
-    threshold: usize,
+fn print_corrections(
+    suggestions: &[Suggestion],
+    corrections: &[Correction],
+    from_file: &[Suggestion],
 ) {
     println!(
         "{}",
@@ -151,220 +753,369 @@ fn apply_fuzzy_corrections(
     println!("Jämför styrelsemedlemmar mot FUM-listan för att hitta stavfel...");
     println!("------------------------------------------------");
 
-    let mut corrections_made = false;
-
-    for board in boards.iter_mut() {
-        if board.year != target_year {
-            continue;
+    for suggestion in suggestions {
+        if from_file.contains(suggestion) {
+            println!(
+                "{} '{}' på {} (enligt korrigeringsfilen)",
+                "[TIDIGARE BESLUT]".cyan().bold(),
+                suggestion.found,
+                suggestion.board
+            );
         }
-        for member in &mut board.members {
-            if correct_names
-                .iter()
-                .any(|n| n.eq_ignore_ascii_case(&member.name))
-            {
-                continue;
-            }
-            let mut best_match: Option<&String> = None;
-            let mut best_dist = usize::MAX;
-            for correct in correct_names {
-                let dist =
-                    strsim::levenshtein(&member.name.to_lowercase(), &correct.to_lowercase());
-                if dist < best_dist {
-                    best_dist = dist;
-                    best_match = Some(correct);
-                }
-            }
+        let correction = corrections.iter().find(|c| {
+            c.board == suggestion.board && c.year == suggestion.year && c.from == suggestion.found
+        });
+        let Some(correction) = correction else {
+            println!("{}", "[AVVISAD]".dimmed().bold());
+            println!(
+                "  '{}' på {} lämnas orört.",
+                suggestion.found, suggestion.board
+            );
+            println!();
+            continue;
+        };
 
-            if let Some(correct) = best_match {
-                if best_dist > 0 && best_dist <= threshold {
-                    println!("{}", "[KORRIGERING]".yellow().bold());
-                    println!("  Plats:    {} ({})", board.name.cyan(), board.year.cyan());
-                    println!("  Hittade:  '{}'", member.name.red());
-                    println!("  Ändrar till: '{}' (FUM-ledamot)", correct.green());
-                    println!("  Avstånd:  {} tecken", best_dist);
-                    println!();
-
-                    member.name = correct.clone();
-                    corrections_made = true;
-                }
-            }
-        }
+        println!("{}", "[KORRIGERING]".yellow().bold());
+        println!(
+            "  Plats:    {} ({})",
+            correction.board.cyan(),
+            correction.year.to_string().cyan()
+        );
+        println!("  Hittade:  '{}'", correction.from.red());
+        println!("  Ändrar till: '{}' (FUM-ledamot)", correction.to.green());
+        println!("  Avstånd:  {} tecken", correction.distance);
+        println!();
     }
 
-    if !corrections_made {
+    if suggestions.is_empty() {
         println!("{}", "[OK] Inga namn behövde korrigeras.".green());
+    } else if corrections.is_empty() {
+        println!("Inga korrigeringar godkändes.");
     }
     println!("------------------------------------------------\n");
 }
 
-fn fetch_content(source: &str) -> String {
-    if source.starts_with("http") {
-        info!("Laddar ner HTML från URL: {}", source);
-        reqwest::blocking::get(source)
-            .and_then(|r| r.text())
-            .expect("Kunde inte läsa svaret från servern")
-    } else {
-        info!("Läser fil: {}", source);
-        fs::read_to_string(source).expect("Kunde inte läsa filen")
+fn print_priority(priority: &[String]) {
+    if priority.is_empty() {
+        return;
+    }
+    println!("{}", "PRIORITERING AKTIVERAD".blue().bold());
+    println!("Följande styrelser behandlas först:");
+    for p in priority {
+        println!("  -> {}", p.yellow());
     }
+    println!("------------------------------------------------\n");
 }
 
-fn find_voting_board<'a>(
-    boards: &'a [Board],
-    base_name: &str,
-    requested_year: Option<&str>,
-) -> Option<&'a Board> {
-    let candidates: Vec<_> = boards.iter().filter(|b| b.name == base_name).collect();
+/// Frågar användaren om ett förslag. `None` betyder att det avvisas.
+///
+/// Frågorna skrivs till stderr så att de inte hamnar i en omdirigerad rapport.
+fn prompt_decision(suggestion: &Suggestion) -> io::Result<Option<String>> {
+    eprintln!("{}", "[FÖRSLAG]".yellow().bold());
+    eprintln!(
+        "  Plats:    {} ({})",
+        suggestion.board.cyan(),
+        suggestion.year.to_string().cyan()
+    );
+    eprintln!("  Hittade:  '{}'", suggestion.found.red());
+    for (i, c) in suggestion.candidates.iter().enumerate() {
+        eprintln!("  {}) {} (avstånd {})", i + 1, c.name.green(), c.distance);
+    }
 
-    if let Some(y) = requested_year {
-        candidates.into_iter().find(|b| b.year == y)
-    } else {
-        candidates.into_iter().max_by_key(|b| b.year.clone())
-    }
-}
-
-fn process_absences(valid_fum_names: &[String], requested_absent: &[String]) -> HashSet<String> {
-    let mut absent_set = HashSet::new();
-    let valid_names_set: HashSet<&String> = valid_fum_names.iter().collect();
-
-    if !requested_absent.is_empty() {
-        println!("{}", "FRÅNVAROHANTERING".blue().bold());
-        for name in requested_absent {
-            let trimmed = name.trim();
-            if let Some(real_name) = valid_names_set
-                .iter()
-                .find(|n| n.eq_ignore_ascii_case(trimmed))
-            {
-                println!("  [INFO] {} markeras som frånvarande.", real_name.yellow());
-                absent_set.insert(real_name.to_string());
-            } else {
-                println!(
-                    "  {}",
-                    format!(
-                        "[FEL] Kunde inte hitta '{}' i FUM-listan. Kontrollera stavning!",
-                        trimmed
-                    )
-                    .red()
-                );
-            }
+    let stdin = io::stdin();
+    loop {
+        eprint!(
+            "  Välj [Enter = 1, n = avvisa, 1-{}]: ",
+            suggestion.candidates.len()
+        );
+        io::stderr().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            // Ingen indata kvar, gör ingen ändring.
+            return Ok(None);
+        }
+        match line.trim().to_lowercase().as_str() {
+            "" | "j" | "ja" | "y" => return Ok(Some(suggestion.candidates[0].name.clone())),
+            "n" | "nej" => return Ok(None),
+            choice => match choice.parse::<usize>() {
+                Ok(n) if (1..=suggestion.candidates.len()).contains(&n) => {
+                    return Ok(Some(suggestion.candidates[n - 1].name.clone()));
+                }
+                _ => eprintln!("  {}", "Ogiltigt val.".red()),
+            },
         }
-        println!();
     }
-    absent_set
 }
 
-fn print_fum_info(name: &str, year: &str, members: &[String], absent_set: &HashSet<String>) {
+fn export_boards(boards: &[Board], path: &str) -> Result<(), Error> {
+    let Some(format) = RosterFormat::from_path(path) else {
+        return Err(Error::Output(format!(
+            "Okänt exportformat för '{}', använd .json eller .toml.",
+            path
+        )));
+    };
+    render_roster(boards, format)
+        .and_then(|text| Ok(fs::write(path, text)?))
+        .map_err(|e| Error::Output(format!("Kunde inte spara roster: {}", e)))?;
+    info!("Sparade {} styrelser till {}.", boards.len(), path);
+    Ok(())
+}
+
+fn print_absences(absent: &[String]) {
+    if absent.is_empty() {
+        return;
+    }
+    println!("{}", "FRÅNVAROHANTERING".blue().bold());
+    for name in absent {
+        println!("  [INFO] {} markeras som frånvarande.", name.yellow());
+    }
+    println!();
+}
+
+fn print_fum_info(board: &Board, absent: &[String]) {
     println!("------------------------------------------------");
-    println!("RÖSTANDE ORGAN: {} ({})", name.green().bold(), year.green());
+    println!(
+        "RÖSTANDE ORGAN: {} ({})",
+        board.name.green().bold(),
+        board.year.to_string().green()
+    );
 
-    let mut sorted_members = members.to_vec();
+    let mut sorted_members: Vec<&str> = board.seated_members().map(|m| m.name.as_str()).collect();
     sorted_members.sort();
 
     println!("Ledamöter (Totalt {}):", sorted_members.len());
     for name in sorted_members {
-        if absent_set.contains(&name) {
-            println!("  - {} {}", name.dimmed(), "(FRÅNVARANDE)".red());
-        } else {
-            println!("  * {}", name);
+        print_attendance_line(name, absent);
+    }
+
+    if board.seated_deputies().next().is_some() {
+        println!("Ersättare (i prioritetsordning):");
+        for m in board.seated_deputies() {
+            print_attendance_line(&m.name, absent);
         }
     }
 }
 
-fn print_analysis_results(analysis: &AnalysisResult, absent_set: &HashSet<String>) {
+fn print_attendance_line(name: &str, absent: &[String]) {
+    if absent.iter().any(|a| a == name) {
+        println!("  - {} {}", name.dimmed(), "(FRÅNVARANDE)".red());
+    } else {
+        println!("  * {}", name);
+    }
+}
+
+fn print_analysis_results(report: &Report) {
     println!("{}", "ANALYSRESULTAT".blue().bold());
-    println!("Mandat i FUM: {}", analysis.total_seats);
+    println!("Mandat i FUM: {}", report.total_seats);
+    print_seat_warnings(&report.seats);
     println!(
         "Närvarande på mötet: {}",
-        analysis.present_count.to_string().bold()
+        report.present_count.to_string().bold()
     );
+    println!("Kvorumregel: {}", report.quorum_description);
     println!(
         "Kvorumgräns (krävs för beslut): {}\n",
-        analysis.quorum_limit.to_string().bold()
+        report.quorum_limit.to_string().bold()
     );
+    if !report.optimal {
+        println!(
+            "{}\n",
+            "(Tidsgränsen nåddes: gruppindelningen är inte bevisat minimal)".yellow()
+        );
+    }
+    if !report.corrections.is_empty() {
+        println!("Resultatet bygger på följande namnkorrigeringar:");
+        for c in &report.corrections {
+            println!("  - {} ({}): '{}' -> '{}'", c.board, c.year, c.from, c.to);
+        }
+        println!();
+    }
 
-    if analysis.present_count < analysis.quorum_limit {
+    if !report.quorate {
         println!("{}", "!!! MÖTET EJ BESLUTSMÄSSIGT !!!".red().bold().blink());
         println!(
-            "För få närvarande ledamöter ({}) för att nå kvorum ({}).",
-            analysis.present_count, analysis.quorum_limit
+            "För få röstberättigade, ersättare inräknade ({}), för att nå kvorum ({}).",
+            report.seated, report.quorum_limit
         );
         return;
     }
 
-    if !analysis.impossible.is_empty() {
+    if !report.impossible.is_empty() {
         println!(
             "{}",
             "!!! VARNING: Följande kan INTE tas upp (för få röstberättigade kvar) !!!"
                 .red()
                 .bold()
         );
-
-        let mut sorted_impossible = analysis.impossible.clone();
-        sorted_impossible.sort_by(|a, b| a.name.cmp(&b.name));
-
-        for b in &sorted_impossible {
-            println!("  - {} ({})", b.name.red(), b.year.red());
+        for b in &report.impossible {
+            println!("  - {} ({})", b.name.red(), b.year.to_string().red());
+            print_causes(b);
         }
         println!();
+        if let Some(call_in) = &report.call_in {
+            print_call_in(call_in);
+        }
     }
 
-    for (i, group) in analysis.groups.iter().enumerate() {
-        let conflicts_present_count = group
-            .conflicted_members
-            .iter()
-            .filter(|n| !absent_set.contains(*n))
-            .count();
-
-        let eligible = analysis.present_count - conflicts_present_count;
-        let group_header = format!("GRUPP {}: ({} röstberättigade)", i + 1, eligible);
+    for (i, group) in report.groups.iter().enumerate() {
+        // Med flera granskade år står gruppens år med i rubriken.
+        let group_header = if report.audited_years.len() > 1 {
+            format!(
+                "GRUPP {} [{}]: ({} röstberättigade)",
+                i + 1,
+                join_years(&group.years),
+                group.eligible
+            )
+        } else {
+            format!("GRUPP {}: ({} röstberättigade)", i + 1, group.eligible)
+        };
 
         println!("{}", group_header.green().bold());
-        println!("  (Krav för beslut: {} st)", analysis.quorum_limit);
-
-        let mut conflicts: Vec<_> = group.conflicted_members.iter().collect();
-        conflicts.sort();
+        println!("  (Krav för beslut: {} st)", report.quorum_limit);
+        print_margin(group);
 
         println!("  Jäviga ledamöter i denna grupp:");
-        if conflicts.is_empty() {
+        if group.conflicted.is_empty() {
             println!("    (Inga)");
         } else {
-            for name in conflicts {
-                if absent_set.contains(name) {
-                    println!("    - {} {}", name.dimmed(), "(Frånvarande)".italic());
+            for person in &group.conflicted {
+                if person.present {
+                    println!("    - {} (Närvarande, får ej rösta)", person.name.red());
                 } else {
-                    println!("    - {} {}", name.red(), "(Närvarande, får ej rösta)");
+                    println!(
+                        "    - {} {}",
+                        person.name.dimmed(),
+                        "(Frånvarande)".italic()
+                    );
+                }
+            }
+        }
+        if !group.substitutions.is_empty() {
+            println!("  Tjänstgörande ersättare:");
+            for sub in &group.substitutions {
+                match &sub.replaces {
+                    Some(replaces) => println!(
+                        "    - {} ersätter {}",
+                        sub.deputy.name.green(),
+                        replaces.name
+                    ),
+                    None => println!("    - {} på vakant mandat", sub.deputy.name.green()),
                 }
             }
         }
         println!();
         println!("  Styrelser:");
 
-        let mut sorted_boards = group.boards.clone();
-        sorted_boards.sort_by(|a, b| a.name.cmp(&b.name));
-
-        for b in &sorted_boards {
-            println!("    * {} ({})", b.name.cyan(), b.year.white().dimmed());
-            let board_conflicts: Vec<_> = b
-                .members
-                .iter()
-                .filter(|m| group.conflicted_members.contains(&m.name))
-                .map(|m| m.name.as_str())
-                .collect();
-            if !board_conflicts.is_empty() {
-                let conflict_str = board_conflicts.join(", ");
-                println!("      -> Jäv: {}", conflict_str.red());
+        for b in &group.boards {
+            println!(
+                "    * {} ({})",
+                b.name.cyan(),
+                b.year.to_string().white().dimmed()
+            );
+            if !b.conflicts.is_empty() {
+                let names: Vec<&str> = b.conflicts.iter().map(|p| p.name.as_str()).collect();
+                println!("      -> Jäv: {}", names.join(", ").red());
             }
+            print_causes(b);
         }
         println!("------------------------------------------------");
     }
 }
 
-fn get_previous_year(current_year: &str) -> Result<String, String> {
-    let parts: Vec<&str> = current_year.split('/').collect();
-    if parts.len() != 2 {
-        return Err("Fel format på årtal".to_string());
+fn print_margin(group: &GroupReport) {
+    let line = format!("  Marginal: {} röstberättigade utöver kvorum", group.margin);
+    if group.margin == 0 {
+        println!("{}", line.yellow());
+    } else {
+        println!("{}", line);
+    }
+    if !group.breaking.is_empty() {
+        let names: Vec<&str> = group.breaking.iter().map(|p| p.name.as_str()).collect();
+        println!("  Får inte lämna mötet: {}", names.join(", ").yellow());
+    }
+}
+
+/// Orsaken bakom varje jäv, när en deklarerad relation, en policyregel
+/// eller ett annat standardutfall än jäv har spelat in.
+fn print_causes(board: &BoardReport) {
+    let default_only = board.causes.iter().all(|c| {
+        matches!(c.reason, ConflictReason::Membership { .. })
+            && c.rule.is_none()
+            && c.verdict == Verdict::Conflict
+    });
+    if default_only {
+        return;
+    }
+    for c in &board.causes {
+        let origin = match (&c.reason, &c.rule) {
+            (ConflictReason::Declared { .. }, _) => "deklarerad relation".to_string(),
+            (_, Some(rule)) => format!("enligt {}", rule),
+            (_, None) => "enligt standardutfallet".to_string(),
+        };
+        let line = format!("{} ({}), {}", c.person.name, c.reason.describe(), origin);
+        match c.verdict {
+            Verdict::Conflict => println!("        jävig: {}", line.red()),
+            Verdict::NoConflict => println!("        ej jävig: {}", line.dimmed()),
+            Verdict::Warn => println!("        pröva: {}", line.yellow()),
+        }
+    }
+}
+
+fn print_call_in(call_in: &CallInReport) {
+    let list = |people: &[PersonReport]| {
+        let names: Vec<&str> = people.iter().map(|p| p.name.as_str()).collect();
+        names.join(", ").yellow().to_string()
+    };
+    let names = |call: &CallIn<PersonReport>| match call {
+        CallIn::Found(people) => list(people),
+        CallIn::Unreachable => "räcker inte ens om alla frånvarande kommer"
+            .italic()
+            .to_string(),
+        CallIn::SearchExhausted(people) => format!(
+            "{} {}",
+            list(people),
+            "(alla frånvarande räcker, sökningen efter färre avbröts)".italic()
+        ),
+    };
+    println!(
+        "{}",
+        "RING IN (minsta antal frånvarande som behövs)"
+            .blue()
+            .bold()
+    );
+    for b in &call_in.boards {
+        println!("  {} ({}): {}", b.name, b.year, names(&b.call));
+    }
+    if call_in.boards.len() > 1 {
+        println!("  Alla styrelser på en gång: {}", names(&call_in.all));
+    }
+    println!();
+}
+
+fn print_seat_warnings(seats: &SeatSummary) {
+    if seats.vacant > 0 {
+        println!("  Varav vakanta: {}", seats.vacant.to_string().yellow());
+    }
+    if let Some(declared) = seats.declared
+        && seats.mismatch()
+    {
+        println!(
+            "  {}",
+            format!(
+                "[VARNING] Rostern har {} poster men stadgarna anger {} mandat.",
+                seats.roster, declared
+            )
+            .yellow()
+        );
+    }
+    for name in &seats.duplicate_names {
+        println!(
+            "  {}",
+            format!(
+                "[VARNING] '{}' finns på flera poster och räknas som en person.",
+                name
+            )
+            .yellow()
+        );
     }
-    let s: i32 = parts[0].parse().map_err(|_| "Fel startår")?;
-    let e: i32 = parts[1].parse().map_err(|_| "Fel slutår")?;
-    Ok(format!("{}/{}", s - 1, e - 1))
 }
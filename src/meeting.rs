@@ -0,0 +1,246 @@
+//! Mötesläge: närvaron ändras under mötet och återstående styrelser planeras om.
+//!
+//! En `Meeting` utgår från en färdig granskning och håller styrelserna i
+//! minnet. Varje ändring (någon kommer, någon går, en grupp är avklarad)
+//! ger en ny gruppindelning för de styrelser som ännu inte har beslutats.
+//! Redan fattade beslut ligger fast och prövas inte om. När någon kommer
+//! eller går markeras de beslutade grupper där personen röstade eller var
+//! jävig, så att mötet ser vilka beslut ändringen berör.
+
+use crate::analyzer::{AnalysisOptions, analyze_voting_groups_with, suggest_call_ins};
+use crate::audit::AuditResult;
+use crate::corrections::Correction;
+use crate::identity::PersonId;
+use crate::report::{GroupReport, PersonReport, Report};
+use crate::types::Board;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Ett kommando i mötesläget.
+///
+/// | Text             | Kommando                                |
+/// |------------------|-----------------------------------------|
+/// | `arrive <namn>`  | personen har kommit till mötet          |
+/// | `leave <namn>`   | personen har lämnat mötet               |
+/// | `done group <n>` | grupp `n` i den aktuella planen är klar |
+/// | `plan`           | visa den aktuella planen                |
+/// | `help`           | visa kommandona                         |
+/// | `quit`           | avsluta mötesläget                      |
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MeetingCommand {
+    Arrive(String),
+    Leave(String),
+    Done(usize),
+    Plan,
+    Help,
+    Quit,
+}
+
+impl FromStr for MeetingCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (word, rest) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let rest = rest.trim();
+        let name = |command: fn(String) -> MeetingCommand| {
+            if rest.is_empty() {
+                Err(format!("'{}' behöver ett namn", word))
+            } else {
+                Ok(command(rest.to_string()))
+            }
+        };
+        match word.to_lowercase().as_str() {
+            "arrive" => name(MeetingCommand::Arrive),
+            "leave" => name(MeetingCommand::Leave),
+            "done" => {
+                let number = rest
+                    .strip_prefix("group")
+                    .unwrap_or(rest)
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Förväntade 'done group <nummer>', fick '{}'", s))?;
+                Ok(MeetingCommand::Done(number))
+            }
+            "plan" if rest.is_empty() => Ok(MeetingCommand::Plan),
+            "help" | "?" if rest.is_empty() => Ok(MeetingCommand::Help),
+            "quit" | "exit" if rest.is_empty() => Ok(MeetingCommand::Quit),
+            _ => Err(format!("Okänt kommando '{}', skriv 'help'", s)),
+        }
+    }
+}
+
+/// En grupp som mötet har fattat beslut om.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecidedGroup {
+    /// Ordningsnummer bland besluten, med början på 1.
+    pub number: usize,
+    pub group: GroupReport,
+    /// Satt när den senaste närvaroändringen gällde någon som röstade i
+    /// gruppen eller var jävig i den. Beslutet står fast ändå.
+    pub touched: Option<Involvement>,
+}
+
+/// Hur personen i den senaste närvaroändringen hör till en beslutad grupp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Involvement {
+    Voter(String),
+    Conflicted(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Meeting {
+    voting_body: Board,
+    /// Alla granskade styrelser i prioritetsordning, även de beslutade.
+    boards: Vec<Board>,
+    options: AnalysisOptions,
+    corrections: Vec<Correction>,
+    /// Frånvarande som de står i det röstande organets roster.
+    absent: Vec<String>,
+    decided: Vec<DecidedGroup>,
+    plan: Report,
+}
+
+impl Meeting {
+    /// Startar mötet med granskningens styrelser, korrigeringar och frånvaro.
+    /// `options` bör vara samma som granskningen kördes med.
+    pub fn new(audit: &AuditResult, options: AnalysisOptions) -> Self {
+        Meeting {
+            voting_body: audit.voting_body.clone(),
+            boards: audit.boards.clone(),
+            options,
+            corrections: audit.corrections.clone(),
+            absent: audit.absent.clone(),
+            decided: Vec::new(),
+            plan: audit.report.clone(),
+        }
+    }
+
+    /// Gruppindelningen för de styrelser som ännu inte har beslutats.
+    pub fn plan(&self) -> &Report {
+        &self.plan
+    }
+
+    /// Beslutade grupper i den ordning de avklarades.
+    pub fn decided(&self) -> &[DecidedGroup] {
+        &self.decided
+    }
+
+    pub fn absent(&self) -> &[String] {
+        &self.absent
+    }
+
+    /// Sant när alla styrelser har beslutats.
+    pub fn is_finished(&self) -> bool {
+        self.plan.groups.is_empty() && self.plan.impossible.is_empty()
+    }
+
+    /// Markerar en frånvarande som närvarande. Returnerar namnet som det står
+    /// i rostern.
+    pub fn arrive(&mut self, name: &str) -> Result<String, String> {
+        let real_name = self.roster_name(name)?;
+        let Some(i) = self.absent.iter().position(|a| *a == real_name) else {
+            return Err(format!("{} är redan närvarande", real_name));
+        };
+        self.absent.remove(i);
+        self.touch(&real_name);
+        self.replan();
+        Ok(real_name)
+    }
+
+    /// Markerar en närvarande som frånvarande. Returnerar namnet som det står
+    /// i rostern.
+    pub fn leave(&mut self, name: &str) -> Result<String, String> {
+        let real_name = self.roster_name(name)?;
+        if self.absent.contains(&real_name) {
+            return Err(format!("{} är redan frånvarande", real_name));
+        }
+        self.absent.push(real_name.clone());
+        self.touch(&real_name);
+        self.replan();
+        Ok(real_name)
+    }
+
+    /// Markerar grupp `number` (räknat från 1) i den aktuella planen som
+    /// beslutad och planerar om resten.
+    pub fn done(&mut self, number: usize) -> Result<&DecidedGroup, String> {
+        if !self.plan.quorate {
+            return Err("Mötet är inte beslutsmässigt".to_string());
+        }
+        let group = number
+            .checked_sub(1)
+            .and_then(|i| self.plan.groups.get(i))
+            .ok_or_else(|| format!("Planen har ingen grupp {}", number))?
+            .clone();
+        for d in &mut self.decided {
+            d.touched = None;
+        }
+        self.decided.push(DecidedGroup {
+            number: self.decided.len() + 1,
+            group,
+            touched: None,
+        });
+        self.replan();
+        Ok(self.decided.last().expect("beslutet lades just till"))
+    }
+
+    fn roster_name(&self, name: &str) -> Result<String, String> {
+        self.options
+            .directory
+            .find_on(&self.voting_body, name.trim())
+            .map(|m| m.name.clone())
+            .ok_or_else(|| {
+                format!(
+                    "Kunde inte hitta '{}' i {}",
+                    name.trim(),
+                    self.voting_body.name
+                )
+            })
+    }
+
+    /// Markerar de beslutade grupper där `name` röstade eller var jävig.
+    fn touch(&mut self, name: &str) {
+        let id = self.options.directory.resolve(name, &self.voting_body.name);
+        let find =
+            |people: &[PersonReport]| people.iter().find(|p| p.id == id).map(|p| p.name.clone());
+        for d in &mut self.decided {
+            d.touched = find(&d.group.conflicted)
+                .map(Involvement::Conflicted)
+                .or_else(|| find(&d.group.voters).map(Involvement::Voter));
+        }
+    }
+
+    fn is_decided(&self, board: &Board) -> bool {
+        self.decided.iter().any(|d| in_group(&d.group, board))
+    }
+
+    fn replan(&mut self) {
+        let directory = &self.options.directory;
+        let absent_ids: HashSet<PersonId> = self
+            .absent
+            .iter()
+            .map(|n| directory.resolve(n, &self.voting_body.name))
+            .collect();
+        let remaining: Vec<&Board> = self.boards.iter().filter(|b| !self.is_decided(b)).collect();
+        let analysis =
+            analyze_voting_groups_with(&self.voting_body, &remaining, &absent_ids, &self.options);
+        let mut plan = Report::new(&analysis, &self.voting_body, &absent_ids, &self.corrections);
+        if !analysis.impossible.is_empty() {
+            let call_in = suggest_call_ins(
+                &self.voting_body,
+                &analysis.impossible,
+                &absent_ids,
+                &self.options,
+            );
+            plan = plan.with_call_in(&call_in, &analysis);
+        }
+        self.plan = plan;
+    }
+}
+
+fn in_group(group: &GroupReport, board: &Board) -> bool {
+    group
+        .boards
+        .iter()
+        .any(|b| b.name == board.name && b.year == board.year)
+}
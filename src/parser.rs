@@ -1,11 +1,89 @@
 use crate::types::{Board, Member};
+use crate::year::VerksamhetsAr;
 use anyhow::Result;
 use scraper::{ElementRef, Html, Selector};
 use std::collections::HashMap;
+use std::fmt;
+use winnow::ascii::{line_ending, multispace0, space0};
+use winnow::combinator::{alt, eof, not, peek, preceded, repeat, terminated};
+use winnow::prelude::*;
+use winnow::token::take_till;
 
-pub fn parse_html_boards(html_content: &str) -> Result<Vec<Board>> {
+/// Styrelserna i en källa och det parsern hoppade över på vägen.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedBoards {
+    pub boards: Vec<Board>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Något i källan som parsern hoppade över eller fick gissa sig till.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// DOM-sökväg eller textutdrag som pekar ut stället i källan.
+    pub location: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// En flik eller ett textblock utan innehåll eller utan några poster.
+    SkippedTab,
+    /// En flik utan rubrik.
+    EmptyTitle,
+    /// En styrelse utan årtal, som hamnar under "Okänt år".
+    UnknownYear,
+    /// En styrelse som redan fanns för samma år och därför ignorerades.
+    DuplicateBoard,
+    /// En rad utan kolon före första posten, som ignorerades.
+    OrphanLine,
+    /// En rad utan kolon som lades till föregående namn.
+    ContinuationLine,
+    /// En rubrik utan årtal, som lika gärna kan vara ett namn som fortsätter
+    /// från föregående rad.
+    AmbiguousHeader,
+}
+
+impl Diagnostic {
+    fn new(kind: DiagnosticKind, location: impl Into<String>) -> Self {
+        Diagnostic {
+            kind,
+            location: location.into(),
+        }
+    }
+
+    /// Hur allvarlig diagnostiken är. Förlorad data är en varning, medan
+    /// sådant som sidan normalt innehåller bara syns med högre detaljnivå.
+    pub fn level(&self) -> tracing::Level {
+        match self.kind {
+            DiagnosticKind::SkippedTab
+            | DiagnosticKind::UnknownYear
+            | DiagnosticKind::OrphanLine
+            | DiagnosticKind::AmbiguousHeader => tracing::Level::WARN,
+            DiagnosticKind::EmptyTitle | DiagnosticKind::DuplicateBoard => tracing::Level::DEBUG,
+            DiagnosticKind::ContinuationLine => tracing::Level::TRACE,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            DiagnosticKind::SkippedTab => "Hoppade över styrelse utan poster",
+            DiagnosticKind::EmptyTitle => "Hoppade över flik utan rubrik",
+            DiagnosticKind::UnknownYear => "Styrelse utan årtal",
+            DiagnosticKind::DuplicateBoard => "Ignorerade dubblett",
+            DiagnosticKind::OrphanLine => "Ignorerade rad utan kolon",
+            DiagnosticKind::ContinuationLine => "Slog ihop rad utan kolon med föregående namn",
+            DiagnosticKind::AmbiguousHeader => "Tolkade rad utan årtal som ny styrelse",
+        };
+        write!(f, "{}: {}", message, self.location)
+    }
+}
+
+pub fn parse_html_boards(html_content: &str) -> Result<ParsedBoards> {
     let document = Html::parse_document(html_content);
-    let mut boards_map: HashMap<(String, String), Board> = HashMap::new();
+    let mut boards_map: HashMap<(String, VerksamhetsAr), Board> = HashMap::new();
+    let mut diagnostics = Vec::new();
 
     let section_selector = Selector::parse("section").unwrap();
     let heading_selector = Selector::parse("h1, h2, h3").unwrap();
@@ -13,21 +91,33 @@ pub fn parse_html_boards(html_content: &str) -> Result<Vec<Board>> {
     let tab_wrapper_selector = Selector::parse(".elementor-tabs-wrapper").unwrap();
     let tab_content_wrapper_selector = Selector::parse(".elementor-tabs-content-wrapper").unwrap();
 
-    let year_regex = regex::Regex::new(r"20\d{2}/20\d{2}").unwrap();
-
-    println!("INFO: Startar parsing...");
+    // Årtal i rubrikerna skrivs med snedstreck, "2024/2025" eller "24/25".
+    // Förkortningen "2425" räknas bara efter ordet "verksamhetsår", så att
+    // andra tal i rubrikerna inte tas för årtal.
+    let year_regex = regex::Regex::new(
+        r"(?i)\b\d{2}(?:\d{2})?/\d{2}(?:\d{2})?\b|verksamhetsår(?:et)?\s+(\d{4})\b",
+    )
+    .unwrap();
 
-    for section in document.select(&section_selector) {
-        let mut current_year = "Okänt år".to_string();
+    for (s, section) in document.select(&section_selector).enumerate() {
+        // Den första rubriken med ett brutet verksamhetsår gäller för sektionen.
+        let current_year = section
+            .select(&heading_selector)
+            .find_map(|heading| {
+                let text = heading.text().collect::<Vec<_>>().concat();
+                year_regex.captures_iter(&text).find_map(|c| {
+                    let token = c.get(1).unwrap_or_else(|| c.get(0).unwrap());
+                    token
+                        .as_str()
+                        .parse::<VerksamhetsAr>()
+                        .ok()
+                        .filter(|y| matches!(y, VerksamhetsAr::Split { .. }))
+                })
+            })
+            .unwrap_or(VerksamhetsAr::Unknown);
 
-        for heading in section.select(&heading_selector) {
-            let text = heading.text().collect::<Vec<_>>().concat();
-            if let Some(mat) = year_regex.find(&text) {
-                current_year = mat.as_str().to_string();
-            }
-        }
-
-        for widget in section.select(&widget_tabs_selector) {
+        for (w, widget) in section.select(&widget_tabs_selector).enumerate() {
+            let widget_path = format!("section[{}] > .elementor-widget-tabs[{}]", s, w);
             let tab_wrapper_opt = widget.select(&tab_wrapper_selector).next();
             let content_wrapper_opt = widget.select(&tab_content_wrapper_selector).next();
 
@@ -42,36 +132,68 @@ pub fn parse_html_boards(html_content: &str) -> Result<Vec<Board>> {
                     .collect();
 
                 for (i, title) in titles.iter().enumerate() {
-                    if title.is_empty() { continue; }
+                    let tab_path = format!("{} > .elementor-tab-desktop-title[{}]", widget_path, i);
+                    if title.is_empty() {
+                        diagnostics.push(Diagnostic::new(DiagnosticKind::EmptyTitle, tab_path));
+                        continue;
+                    }
+                    let tab_path = format!("{} \"{}\"", tab_path, title);
 
                     if let Some(content_node) = contents.get(i) {
-                        let key = (title.clone(), current_year.clone());
+                        let key = (title.clone(), current_year);
 
-                        if let Some(existing) = boards_map.get(&key) {
-                            if !existing.members.is_empty() {
-                                continue;
-                            }
+                        if let Some(existing) = boards_map.get(&key)
+                            && !existing.members.is_empty()
+                        {
+                            diagnostics.push(Diagnostic::new(
+                                DiagnosticKind::DuplicateBoard,
+                                format!("{} ({})", tab_path, current_year),
+                            ));
+                            continue;
                         }
 
                         let raw_text = extract_text_recursive_wrapper(*content_node);
-                        let members = parse_members_multiline(&raw_text);
+                        let members =
+                            parse_members_multiline(&raw_text, &tab_path, &mut diagnostics);
 
                         if !members.is_empty() {
+                            if current_year.is_unknown() {
+                                diagnostics.push(Diagnostic::new(
+                                    DiagnosticKind::UnknownYear,
+                                    tab_path.clone(),
+                                ));
+                            }
+                            let (members, deputies) = split_deputies(members);
                             boards_map.insert(key, Board {
                                 name: title.clone(),
-                                year: current_year.clone(),
+                                year: current_year,
                                 members,
+                                seats: None,
+                                deputies,
                             });
+                        } else {
+                            diagnostics.push(Diagnostic::new(DiagnosticKind::SkippedTab, tab_path));
                         }
+                    } else {
+                        diagnostics.push(Diagnostic::new(
+                            DiagnosticKind::SkippedTab,
+                            format!("{} (innehåll saknas)", tab_path),
+                        ));
                     }
                 }
+            } else {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::SkippedTab,
+                    format!("{} (flikar eller innehåll saknas)", widget_path),
+                ));
             }
         }
     }
 
-    let result: Vec<Board> = boards_map.into_values().collect();
-    println!("INFO: Totalt {} unika styrelser parsade.", result.len());
-    Ok(result)
+    Ok(ParsedBoards {
+        boards: boards_map.into_values().collect(),
+        diagnostics,
+    })
 }
 
 fn extract_text_recursive_wrapper(element: ElementRef) -> String {
@@ -96,7 +218,11 @@ fn extract_text_recursive(element: ElementRef, buffer: &mut String) {
 }
 
 // NY LOGIK: Hanterar namn som är uppdelade på flera rader
-fn parse_members_multiline(input: &str) -> Vec<Member> {
+fn parse_members_multiline(
+    input: &str,
+    location: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<Member> {
     let mut members = Vec::new();
     let mut current_member: Option<Member> = None;
 
@@ -120,11 +246,16 @@ fn parse_members_multiline(input: &str) -> Vec<Member> {
             }
         } else {
             // Inget kolon. Detta är troligen en fortsättning på föregående namn.
+            let snippet = format!("{}: \"{}\"", location, cleaned);
             if let Some(ref mut m) = current_member {
                 if !m.name.is_empty() {
                     m.name.push(' ');
                 }
-                m.name.push_str(cleaned);
+                // Ett kommatecken sist kommer från en rad som såg ut som en rubrik.
+                m.name.push_str(cleaned.trim_end_matches(',').trim_end());
+                diagnostics.push(Diagnostic::new(DiagnosticKind::ContinuationLine, snippet));
+            } else {
+                diagnostics.push(Diagnostic::new(DiagnosticKind::OrphanLine, snippet));
             }
         }
     }
@@ -134,5 +265,137 @@ fn parse_members_multiline(input: &str) -> Vec<Member> {
         members.push(m);
     }
 
+    // Obesatta poster får ett enhetligt namn så att de aldrig matchas som personer
+    for m in &mut members {
+        if m.is_vacant() {
+            m.name = Member::VACANT.to_string();
+        }
+    }
+
     members
 }
+
+/// Ersättare lyfts ut ur ledamotslistan men behåller sin inbördes ordning.
+fn split_deputies(members: Vec<Member>) -> (Vec<Member>, Vec<Member>) {
+    members.into_iter().partition(|m| !m.is_deputy())
+}
+
+// --- TEXTFORMAT (fum.txt) ---
+//
+// Block som inleds med en rubrikrad "KIDS 2425," följd av "Position: Namn"-rader.
+// Rader utan kolon som inte är rubriker räknas som fortsättning på föregående namn.
+
+/// Parsar rosterformatet som används i `fum.txt`.
+///
+/// Årtalet i rubriken skrivs som ett `VerksamhetsAr`, t.ex. "2425", "2024/2025"
+/// eller kalenderåret "2025". Saknas årtal
+/// ärvs det från föregående styrelse.
+pub fn parse_text_boards(text_content: &str) -> Result<ParsedBoards> {
+    let blocks = text_document
+        .parse(text_content)
+        .map_err(|e| anyhow::anyhow!("Kunde inte parsa textfilen:\n{}", e))?;
+
+    let mut boards: Vec<Board> = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut last_year: Option<VerksamhetsAr> = None;
+
+    for ((name, year), body) in blocks {
+        if year.is_none() && last_year.is_some() {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::AmbiguousHeader,
+                format!("\"{}\"", name),
+            ));
+        }
+        let year = match year.or(last_year) {
+            Some(y) => y,
+            None => {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::UnknownYear,
+                    format!("\"{}\"", name),
+                ));
+                VerksamhetsAr::Unknown
+            }
+        };
+        last_year = Some(year);
+
+        let location = format!("\"{} {}\"", name, year);
+        if boards.iter().any(|b| b.name == name && b.year == year) {
+            diagnostics.push(Diagnostic::new(DiagnosticKind::DuplicateBoard, location));
+            continue;
+        }
+        let members = parse_members_multiline(&body, &location, &mut diagnostics);
+        if !members.is_empty() {
+            let (members, deputies) = split_deputies(members);
+            boards.push(Board {
+                name,
+                year,
+                members,
+                seats: None,
+                deputies,
+            });
+        } else {
+            diagnostics.push(Diagnostic::new(DiagnosticKind::SkippedTab, location));
+        }
+    }
+
+    Ok(ParsedBoards {
+        boards,
+        diagnostics,
+    })
+}
+
+type TextBlock = ((String, Option<VerksamhetsAr>), String);
+
+fn text_document(input: &mut &str) -> ModalResult<Vec<TextBlock>> {
+    preceded(multispace0, repeat(0.., text_block)).parse_next(input)
+}
+
+fn text_block(input: &mut &str) -> ModalResult<TextBlock> {
+    let header = header_line.parse_next(input)?;
+    let lines: Vec<&str> = repeat(0.., body_line).parse_next(input)?;
+    Ok((header, lines.join("\n")))
+}
+
+/// En rubrikrad: text utan kolon som avslutas med ett kommatecken. Utan
+/// årtal måste nästa rad vara en "Position: Namn"-rad, annars räknas raden
+/// som fortsättning på föregående namn, t.ex. "Anna\nAndersson,".
+fn header_line(input: &mut &str) -> ModalResult<(String, Option<VerksamhetsAr>)> {
+    let title = terminated(
+        take_till(1.., [',', ':', '\n', '\r']),
+        (',', space0, line_end),
+    )
+    .parse_next(input)?;
+    let header = split_header_year(title.trim());
+    if header.1.is_none() {
+        peek(preceded(multispace0, position_line)).parse_next(input)?;
+    }
+    Ok(header)
+}
+
+fn position_line(input: &mut &str) -> ModalResult<()> {
+    (take_till(1.., [':', '\n', '\r']), ':')
+        .void()
+        .parse_next(input)
+}
+
+fn body_line<'a>(input: &mut &'a str) -> ModalResult<&'a str> {
+    preceded(
+        (not(eof), not(header_line)),
+        terminated(take_till(0.., ['\n', '\r']), line_end),
+    )
+    .parse_next(input)
+}
+
+fn line_end(input: &mut &str) -> ModalResult<()> {
+    alt((line_ending.void(), eof.void())).parse_next(input)
+}
+
+fn split_header_year(title: &str) -> (String, Option<VerksamhetsAr>) {
+    if let Some((name, last)) = title.rsplit_once(char::is_whitespace)
+        && let Ok(year) = last.parse::<VerksamhetsAr>()
+        && !year.is_unknown()
+    {
+        return (name.trim().to_string(), Some(year));
+    }
+    (title.to_string(), None)
+}
@@ -0,0 +1,314 @@
+//! Uppdelning av styrelser i så få röstgrupper som möjligt.
+//!
+//! Varje styrelse representeras av en bitmask över de närvarande jäviga
+//! ledamöterna. En grupp är giltig så länge `fits` godtar unionen av dess
+//! masker. `fits` måste vara monoton: godtas en mask så godtas alla dess
+//! delmängder.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partition {
+    /// Index in i `masks`, per grupp och i stigande ordning.
+    pub groups: Vec<Vec<usize>>,
+    /// `false` om tidsbudgeten tog slut innan minimum kunde bevisas.
+    pub optimal: bool,
+}
+
+/// Första-passande-uppdelning i given ordning (det gamla beteendet).
+pub fn first_fit(masks: &[u128], fits: &dyn Fn(u128) -> bool) -> Vec<Vec<usize>> {
+    let mut unions: Vec<u128> = Vec::new();
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+
+    for (i, &mask) in masks.iter().enumerate() {
+        match unions.iter().position(|&u| fits(u | mask)) {
+            Some(g) => {
+                unions[g] |= mask;
+                groups[g].push(i);
+            }
+            None => {
+                unions.push(mask);
+                groups.push(vec![i]);
+            }
+        }
+    }
+    groups
+}
+
+/// Exakt branch-and-bound-sökning efter det minsta antalet grupper.
+///
+/// Startar från `first_fit` och returnerar den bästa lösningen som hittats
+/// med `optimal = false` om `budget` överskrids. Varje enskild mask måste
+/// godtas av `fits`.
+pub fn minimum_partition(
+    masks: &[u128],
+    fits: &dyn Fn(u128) -> bool,
+    budget: Duration,
+) -> Partition {
+    let greedy = first_fit(masks, fits);
+
+    // Styrelser utan närvarande jäviga passar i alla grupper och lämnas utanför
+    // sökningen. En styrelse vars jäv är en delmängd av en annans kan alltid
+    // följa med den andra utan att gruppens union växer, så de slås ihop.
+    let mut order: Vec<usize> = (0..masks.len()).filter(|&i| masks[i] != 0).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(masks[i].count_ones()));
+    let free: Vec<usize> = (0..masks.len()).filter(|&i| masks[i] == 0).collect();
+
+    let mut unique: Vec<(u128, Vec<usize>)> = Vec::new();
+    for i in order {
+        let mask = masks[i];
+        match unique.iter_mut().find(|(m, _)| m & mask == mask) {
+            Some(entry) => entry.1.push(i),
+            None => unique.push((mask, vec![i])),
+        }
+    }
+
+    // Styrelser som parvis inte ryms tillsammans måste ligga i olika grupper,
+    // och storleken på en sådan klick är en undre gräns för antalet grupper.
+    let items: Vec<u128> = unique.iter().map(|(m, _)| *m).collect();
+    if items.len() > 128 {
+        return Partition {
+            groups: greedy,
+            optimal: false,
+        };
+    }
+    let deadline = Instant::now() + budget;
+    let clique = incompatible_clique(&items, fits, deadline);
+    let lower_bound = clique.len().max(usize::from(!masks.is_empty()));
+    if greedy.len() <= lower_bound {
+        return Partition {
+            groups: greedy,
+            optimal: true,
+        };
+    }
+
+    let mut search = Search {
+        fits,
+        lower_bound,
+        deadline,
+        nodes: 0,
+        timed_out: false,
+        best_len: greedy.len(),
+        best: None,
+        assignment: vec![None; items.len()],
+        unions: Vec::new(),
+        items,
+    };
+    // Klickens styrelser placeras först, var och en i en egen grupp.
+    for &item in &clique {
+        search.unions.push(search.items[item]);
+        search.assignment[item] = Some(search.unions.len() - 1);
+    }
+    search.run(items_left(&search.assignment));
+
+    let Some(assignment) = search.best else {
+        return Partition {
+            groups: greedy,
+            optimal: !search.timed_out,
+        };
+    };
+
+    let mut groups: Vec<Vec<usize>> = vec![Vec::new(); search.best_len];
+    for (item, g) in assignment.iter().enumerate() {
+        let g = g.expect("alla styrelser är placerade");
+        groups[g].extend(&unique[item].1);
+    }
+    groups[0].extend(free);
+    for group in &mut groups {
+        group.sort_unstable();
+    }
+    // Grupperna ordnas efter sin första styrelse så att inmatningsordningen
+    // (t.ex. --priority) fortfarande avgör vilken grupp som tas först.
+    groups.sort_by_key(|g| g[0]);
+
+    Partition {
+        groups,
+        optimal: !search.timed_out,
+    }
+}
+
+/// Största mängd styrelser som parvis inte ryms i samma grupp.
+///
+/// Exakt sökning med enkel färgningsgräns, avbryts vid `deadline` och
+/// returnerar då den största klick som hittats.
+fn incompatible_clique(
+    items: &[u128],
+    fits: &dyn Fn(u128) -> bool,
+    deadline: Instant,
+) -> Vec<usize> {
+    let n = items.len();
+    let adjacent: Vec<u128> = (0..n)
+        .map(|i| {
+            (0..n)
+                .filter(|&j| j != i && !fits(items[i] | items[j]))
+                .fold(0u128, |acc, j| acc | (1 << j))
+        })
+        .collect();
+
+    let mut best: Vec<usize> = Vec::new();
+    let mut current: Vec<usize> = Vec::new();
+    let all = if n == 128 {
+        u128::MAX
+    } else {
+        (1u128 << n) - 1
+    };
+    expand_clique(&adjacent, all, &mut current, &mut best, deadline);
+    best
+}
+
+fn expand_clique(
+    adjacent: &[u128],
+    mut candidates: u128,
+    current: &mut Vec<usize>,
+    best: &mut Vec<usize>,
+    deadline: Instant,
+) {
+    if candidates == 0 {
+        if current.len() > best.len() {
+            *best = current.clone();
+        }
+        return;
+    }
+    if current.len() + greedy_colour_bound(adjacent, candidates) <= best.len()
+        || Instant::now() >= deadline
+    {
+        return;
+    }
+    while candidates != 0 {
+        if current.len() + candidates.count_ones() as usize <= best.len() {
+            return;
+        }
+        let v = candidates.trailing_zeros() as usize;
+        candidates &= !(1 << v);
+        current.push(v);
+        expand_clique(adjacent, candidates & adjacent[v], current, best, deadline);
+        current.pop();
+    }
+}
+
+/// Antal färger i en girig färgning, en övre gräns för klickstorleken.
+fn greedy_colour_bound(adjacent: &[u128], mut uncoloured: u128) -> usize {
+    let mut colours = 0;
+    while uncoloured != 0 {
+        colours += 1;
+        let mut available = uncoloured;
+        while available != 0 {
+            let v = available.trailing_zeros() as usize;
+            available &= !(1 << v) & !adjacent[v];
+            uncoloured &= !(1 << v);
+        }
+    }
+    colours
+}
+
+fn items_left(assignment: &[Option<usize>]) -> usize {
+    assignment.iter().filter(|a| a.is_none()).count()
+}
+
+struct Search<'a> {
+    items: Vec<u128>,
+    fits: &'a dyn Fn(u128) -> bool,
+    lower_bound: usize,
+    deadline: Instant,
+    nodes: u64,
+    timed_out: bool,
+    best_len: usize,
+    best: Option<Vec<Option<usize>>>,
+    assignment: Vec<Option<usize>>,
+    unions: Vec<u128>,
+}
+
+impl Search<'_> {
+    fn done(&self) -> bool {
+        self.timed_out || self.best_len <= self.lower_bound
+    }
+
+    fn fits(&self, group: usize, item: usize) -> bool {
+        (self.fits)(self.unions[group] | self.items[item])
+    }
+
+    fn run(&mut self, left: usize) {
+        if self.done() || self.unions.len() >= self.best_len {
+            return;
+        }
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(1024) && Instant::now() >= self.deadline {
+            self.timed_out = true;
+            return;
+        }
+        if left == 0 {
+            self.best_len = self.unions.len();
+            self.best = Some(self.assignment.clone());
+            return;
+        }
+
+        // Välj den styrelse som har minst antal möjliga grupper kvar.
+        let mut chosen = None;
+        let mut fewest = usize::MAX;
+        for item in 0..self.items.len() {
+            if self.assignment[item].is_some() {
+                continue;
+            }
+            let options = (0..self.unions.len())
+                .filter(|&g| self.fits(g, item))
+                .count();
+            if options < fewest {
+                fewest = options;
+                chosen = Some(item);
+            }
+        }
+        let Some(item) = chosen else { return };
+
+        // Styrelser som inte ryms i någon befintlig grupp och inte heller med
+        // varandra kräver var sin ny grupp.
+        if fewest == 0 {
+            let stranded: Vec<u128> = (0..self.items.len())
+                .filter(|&i| self.assignment[i].is_none())
+                .filter(|&i| !(0..self.unions.len()).any(|g| self.fits(g, i)))
+                .map(|i| self.items[i])
+                .collect();
+            let needed = incompatible_clique(&stranded, self.fits, self.deadline).len();
+            if self.unions.len() + needed >= self.best_len {
+                return;
+            }
+        }
+
+        // Ryms styrelsens jäv redan helt i en grupp läggs den där utan förgrening.
+        let mask = self.items[item];
+        if let Some(g) = self.unions.iter().position(|&u| u & mask == mask) {
+            self.assignment[item] = Some(g);
+            self.run(left - 1);
+            self.assignment[item] = None;
+            return;
+        }
+
+        // Pröva först de grupper vars union växer minst (best fit).
+        let mut candidates: Vec<usize> = (0..self.unions.len())
+            .filter(|&g| self.fits(g, item))
+            // Grupper med samma union är utbytbara, pröva bara den första.
+            .filter(|&g| !self.unions[..g].contains(&self.unions[g]))
+            .collect();
+        candidates
+            .sort_by_key(|&g| (self.unions[g] | mask).count_ones() - self.unions[g].count_ones());
+
+        for g in candidates {
+            let before = self.unions[g];
+            self.unions[g] |= mask;
+            self.assignment[item] = Some(g);
+            self.run(left - 1);
+            self.assignment[item] = None;
+            self.unions[g] = before;
+            if self.done() {
+                return;
+            }
+        }
+
+        if self.unions.len() + 1 < self.best_len {
+            self.unions.push(self.items[item]);
+            self.assignment[item] = Some(self.unions.len() - 1);
+            self.run(left - 1);
+            self.assignment[item] = None;
+            self.unions.pop();
+        }
+    }
+}
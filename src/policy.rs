@@ -0,0 +1,198 @@
+//! Vilka poster som gör en ledamot i det röstande organet jävig.
+//!
+//! Utan policy räknas varje namnträff mellan det röstande organet och en
+//! granskad styrelse som jäv, oavsett post. Stadgarna kan behandla t.ex.
+//! mentorer, adjungerade och revisorer annorlunda, så en policyfil kopplar
+//! poster (`Member.position`) till ett utfall. Den första regeln som
+//! matchar gäller:
+//!
+//! ```toml
+//! default = "conflict"
+//!
+//! [[rule]]
+//! position = "Revisor"
+//! verdict = "no-conflict"
+//!
+//! [[rule]]
+//! name = "Adjungerade prövas av mötet"
+//! pattern = "(?i)^adjungerad"
+//! verdict = "warn"
+//! ```
+//!
+//! `position` jämförs utan hänsyn till versaler och mellanslag runt om,
+//! `pattern` är ett reguljärt uttryck som söks i posten.
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+/// Vad en namnträff på en viss post innebär.
+///
+/// | Text          | Utfall                                            |
+/// |---------------|---------------------------------------------------|
+/// | `conflict`    | jävig, lämnar lokalen (standard)                  |
+/// | `no-conflict` | inte jävig                                        |
+/// | `warn`        | inte jävig, men mötet bör pröva frågan            |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Verdict {
+    #[default]
+    Conflict,
+    NoConflict,
+    Warn,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Verdict::Conflict => "conflict",
+            Verdict::NoConflict => "no-conflict",
+            Verdict::Warn => "warn",
+        })
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "conflict" => Ok(Verdict::Conflict),
+            "no-conflict" => Ok(Verdict::NoConflict),
+            "warn" => Ok(Verdict::Warn),
+            _ => Err(format!(
+                "Okänt utfall '{}', förväntade conflict, no-conflict eller warn",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Verdict {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Verdict> for String {
+    fn from(verdict: Verdict) -> Self {
+        verdict.to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PolicyRule {
+    /// Namnet som visas i rapporterna, se `PolicyRule::label`.
+    pub name: Option<String>,
+    pub applies_to: PositionMatch,
+    pub verdict: Verdict,
+}
+
+#[derive(Debug, Clone)]
+pub enum PositionMatch {
+    /// Posten i gemener utan mellanslag runt om.
+    Exact(String),
+    Pattern(Regex),
+}
+
+impl PolicyRule {
+    pub fn matches(&self, position: &str) -> bool {
+        match &self.applies_to {
+            PositionMatch::Exact(p) => position.trim().to_lowercase() == *p,
+            PositionMatch::Pattern(re) => re.is_match(position),
+        }
+    }
+
+    /// Regelns namn, eller posten eller mönstret den matchar på.
+    pub fn label(&self) -> String {
+        match (&self.name, &self.applies_to) {
+            (Some(name), _) => name.clone(),
+            (None, PositionMatch::Exact(p)) => format!("post \"{}\"", p),
+            (None, PositionMatch::Pattern(re)) => format!("mönster \"{}\"", re.as_str()),
+        }
+    }
+}
+
+/// Utfallet för en post och regeln som gav det, eller `None` när
+/// standardutfallet gäller.
+#[derive(Debug, Clone, Copy)]
+pub struct Judgement<'p> {
+    pub verdict: Verdict,
+    pub rule: Option<&'p PolicyRule>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConflictPolicy {
+    /// Utfallet för poster som ingen regel matchar.
+    pub default: Verdict,
+    pub rules: Vec<PolicyRule>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    default: Verdict,
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleEntry {
+    name: Option<String>,
+    position: Option<String>,
+    pattern: Option<String>,
+    verdict: Verdict,
+}
+
+impl ConflictPolicy {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Kunde inte läsa jävspolicyn {}", path))?;
+        Self::parse(&text).with_context(|| format!("Ogiltig jävspolicy {}", path))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: PolicyFile = toml::from_str(text)?;
+        let mut rules = Vec::with_capacity(file.rules.len());
+        for (i, entry) in file.rules.into_iter().enumerate() {
+            let applies_to = match (entry.position, entry.pattern) {
+                (Some(position), None) => PositionMatch::Exact(position.trim().to_lowercase()),
+                (None, Some(pattern)) => PositionMatch::Pattern(
+                    Regex::new(&pattern)
+                        .with_context(|| format!("Regel {} har ett ogiltigt mönster", i + 1))?,
+                ),
+                _ => bail!("Regel {} ska ha antingen position eller pattern", i + 1),
+            };
+            rules.push(PolicyRule {
+                name: entry.name,
+                applies_to,
+                verdict: entry.verdict,
+            });
+        }
+        Ok(ConflictPolicy {
+            default: file.default,
+            rules,
+        })
+    }
+
+    /// Utfallet för en namnträff på posten `position`.
+    pub fn judge(&self, position: &str) -> Judgement<'_> {
+        match self.rules.iter().find(|r| r.matches(position)) {
+            Some(rule) => Judgement {
+                verdict: rule.verdict,
+                rule: Some(rule),
+            },
+            None => Judgement {
+                verdict: self.default,
+                rule: None,
+            },
+        }
+    }
+}
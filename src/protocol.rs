@@ -0,0 +1,253 @@
+//! Protokollsutkast i Markdown för punkterna om ansvarsfrihet.
+//!
+//! Varje röstningsgrupp blir en egen paragraf med de som anmäler jäv, de
+//! röstberättigade som är kvar och en platshållare för beslutet. Texterna
+//! kommer från en `ProtocolTemplate` som en organisation kan ersätta helt
+//! eller delvis med en TOML-fil:
+//!
+//! ```toml
+//! first_paragraph = 14
+//! decision = "Mötet beslutar att {board} ({year}) beviljas ansvarsfrihet."
+//! ```
+//!
+//! Platshållare skrivs `{namn}`. Vilka som finns anges på respektive fält.
+
+use crate::analyzer::ConflictReason;
+use crate::report::{BoardReport, Report};
+use crate::year::join_years;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProtocolTemplate {
+    /// Numret på den första paragrafen.
+    pub first_paragraph: usize,
+    /// `{organ}`, `{year}`, `{present}`, `{seats}`, `{quorum_rule}`, `{quorum_limit}`
+    pub header: String,
+    /// Ersätter grupperna när mötet inte är beslutsmässigt.
+    /// `{present}`, `{seated}`, `{quorum_limit}`
+    pub not_quorate: String,
+    /// `{paragraph}`, `{boards}`, `{years}`, `{conflicted}`, `{substitutions}`,
+    /// `{voters}`, `{eligible}`, `{quorum_limit}`, `{decisions}`
+    pub group: String,
+    /// Ett beslut per styrelse i gruppen. `{board}`, `{year}`
+    pub decision: String,
+    /// Styrelser som inte kan behandlas. `{paragraph}`, `{boards}`
+    pub impossible: String,
+    /// Skrivs när en lista är tom.
+    pub empty_list: String,
+    pub footer: String,
+}
+
+impl Default for ProtocolTemplate {
+    fn default() -> Self {
+        ProtocolTemplate {
+            first_paragraph: 1,
+            header: "\
+# Protokollsutkast – {organ} ({year})
+
+Närvarande ledamöter: {present} av {seats} mandat.
+Kvorumregel: {quorum_rule}. Mötet är beslutsmässigt med minst {quorum_limit} röstberättigade.
+"
+            .to_string(),
+            not_quorate: "
+**Mötet är inte beslutsmässigt.** {seated} röstberättigade, ersättare inräknade, räcker inte till kvorum om {quorum_limit}.
+"
+            .to_string(),
+            group: "
+## §{paragraph} Ansvarsfrihet för {boards}
+
+Följande anmäler jäv och lämnar lokalen:
+{conflicted}
+
+Tjänstgörande ersättare:
+{substitutions}
+
+Kvarvarande röstberättigade ({eligible} st):
+{voters}
+
+Mötet konstaterar att {eligible} röstberättigade är närvarande, vilket uppfyller kvorum om {quorum_limit}.
+
+{decisions}
+"
+            .to_string(),
+            decision: "Mötet beslutar att _[bevilja / inte bevilja]_ {board} ({year}) ansvarsfrihet."
+                .to_string(),
+            impossible: "
+## §{paragraph} Bordläggning
+
+Följande styrelser kan inte behandlas eftersom för få röstberättigade återstår:
+{boards}
+
+Mötet beslutar att _[bordlägga / hänskjuta]_ frågorna.
+"
+            .to_string(),
+            empty_list: "- (Inga)".to_string(),
+            footer: String::new(),
+        }
+    }
+}
+
+impl ProtocolTemplate {
+    /// Läser en mall där fält som saknas får standardtexten.
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Kunde inte läsa protokollmallen {}", path))?;
+        toml::from_str(&text).with_context(|| format!("Ogiltig protokollmall {}", path))
+    }
+
+    pub fn render(&self, report: &Report) -> String {
+        let mut out = fill(
+            &self.header,
+            &[
+                ("organ", report.voting_body.name.clone()),
+                ("year", report.voting_body.year.to_string()),
+                ("present", report.present_count.to_string()),
+                ("seats", report.total_seats.to_string()),
+                ("quorum_rule", report.quorum_rule.describe()),
+                ("quorum_limit", report.quorum_limit.to_string()),
+            ],
+        );
+
+        if !report.quorate {
+            out += &fill(
+                &self.not_quorate,
+                &[
+                    ("present", report.present_count.to_string()),
+                    ("seated", report.seated.to_string()),
+                    ("quorum_limit", report.quorum_limit.to_string()),
+                ],
+            );
+            out += &self.footer;
+            return out;
+        }
+
+        let mut paragraph = self.first_paragraph;
+        for group in &report.groups {
+            let decisions: Vec<String> = group
+                .boards
+                .iter()
+                .map(|b| {
+                    fill(
+                        &self.decision,
+                        &[("board", b.name.clone()), ("year", b.year.to_string())],
+                    )
+                })
+                .collect();
+            // Frånvarande jäviga behöver inte lämna lokalen. Jäv som inte
+            // syns i rostrarna står med sin deklarerade orsak.
+            let leaving = group.conflicted.iter().filter(|p| p.present).map(|p| {
+                let mut reasons: Vec<String> = group
+                    .boards
+                    .iter()
+                    .flat_map(|b| &b.causes)
+                    .filter(|c| c.person.id == p.id)
+                    .filter(|c| matches!(c.reason, ConflictReason::Declared { .. }))
+                    .map(|c| c.reason.describe())
+                    .collect();
+                reasons.dedup();
+                if reasons.is_empty() {
+                    p.name.clone()
+                } else {
+                    format!("{} ({})", p.name, reasons.join("; "))
+                }
+            });
+            let substitutions: Vec<String> = group
+                .substitutions
+                .iter()
+                .map(|s| match &s.replaces {
+                    Some(replaces) => format!("{} ersätter {}", s.deputy.name, replaces.name),
+                    None => format!("{} på vakant mandat", s.deputy.name),
+                })
+                .collect();
+
+            out += &fill(
+                &self.group,
+                &[
+                    ("paragraph", paragraph.to_string()),
+                    ("boards", board_names(&group.boards)),
+                    ("years", join_years(&group.years)),
+                    ("conflicted", self.list(leaving)),
+                    ("substitutions", self.list(substitutions)),
+                    (
+                        "voters",
+                        self.list(group.voters.iter().map(|p| p.name.clone())),
+                    ),
+                    ("eligible", group.eligible.to_string()),
+                    ("quorum_limit", report.quorum_limit.to_string()),
+                    ("decisions", decisions.join("\n\n")),
+                ],
+            );
+            paragraph += 1;
+        }
+
+        if !report.impossible.is_empty() {
+            let boards = report.impossible.iter().map(|b| {
+                let names: Vec<&str> = b.conflicts.iter().map(|p| p.name.as_str()).collect();
+                format!("{} ({}), jäv: {}", b.name, b.year, names.join(", "))
+            });
+            out += &fill(
+                &self.impossible,
+                &[
+                    ("paragraph", paragraph.to_string()),
+                    ("boards", self.list(boards)),
+                ],
+            );
+        }
+
+        out += &self.footer;
+        out
+    }
+
+    fn list(&self, items: impl IntoIterator<Item = String>) -> String {
+        let lines: Vec<String> = items.into_iter().map(|i| format!("- {}", i)).collect();
+        if lines.is_empty() {
+            self.empty_list.clone()
+        } else {
+            lines.join("\n")
+        }
+    }
+}
+
+/// "A (2024/2025), B (2024/2025) och C (2024/2025)"
+fn board_names(boards: &[BoardReport]) -> String {
+    let names: Vec<String> = boards
+        .iter()
+        .map(|b| format!("{} ({})", b.name, b.year))
+        .collect();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} och {}", rest.join(", "), last),
+        _ => names.concat(),
+    }
+}
+
+/// Ersätter `{nyckel}` med värdet i ett enda svep, så att namn som innehåller
+/// klamrar inte tolkas som platshållare. Okända platshållare lämnas kvar.
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(key, _)| *key == &after[..end])
+                .map(|(_, value)| (value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
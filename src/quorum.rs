@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Hur många röstberättigade som krävs för att ett beslut ska kunna fattas.
+///
+/// Textformen (se `FromStr`) används både på kommandoraden och i
+/// konfigurationsfilen:
+///
+/// | Text           | Regel                                  |
+/// |----------------|----------------------------------------|
+/// | `half`         | minst hälften av mandaten (standard)   |
+/// | `more-than-half` | mer än hälften av mandaten           |
+/// | `fixed:12`     | ett fast antal                         |
+/// | `2/3`          | andel av mandaten, avrundat uppåt      |
+/// | `present:1/2`  | andel av de närvarande, avrundat uppåt |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum QuorumRule {
+    #[default]
+    AtLeastHalf,
+    MoreThanHalf,
+    Fixed(usize),
+    ShareOfSeats {
+        num: usize,
+        den: usize,
+    },
+    ShareOfPresent {
+        num: usize,
+        den: usize,
+    },
+}
+
+impl QuorumRule {
+    /// Antalet röstberättigade som krävs givet mandat och närvarande, dock
+    /// alltid minst en så att ett tomt möte aldrig är beslutsmässigt.
+    pub fn limit(&self, total_seats: usize, present_count: usize) -> usize {
+        let limit = match *self {
+            QuorumRule::AtLeastHalf => total_seats.div_ceil(2),
+            QuorumRule::MoreThanHalf => total_seats / 2 + 1,
+            QuorumRule::Fixed(n) => n,
+            QuorumRule::ShareOfSeats { num, den } => (total_seats * num).div_ceil(den),
+            QuorumRule::ShareOfPresent { num, den } => (present_count * num).div_ceil(den),
+        };
+        limit.max(1)
+    }
+
+    /// Beskrivning på svenska för rapporterna.
+    pub fn describe(&self) -> String {
+        match *self {
+            QuorumRule::AtLeastHalf => "minst hälften av mandaten".to_string(),
+            QuorumRule::MoreThanHalf => "mer än hälften av mandaten".to_string(),
+            QuorumRule::Fixed(n) => format!("fast antal ({} st)", n),
+            QuorumRule::ShareOfSeats { num, den } => format!("{}/{} av mandaten", num, den),
+            QuorumRule::ShareOfPresent { num, den } => {
+                format!("{}/{} av de närvarande", num, den)
+            }
+        }
+    }
+}
+
+impl fmt::Display for QuorumRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            QuorumRule::AtLeastHalf => write!(f, "half"),
+            QuorumRule::MoreThanHalf => write!(f, "more-than-half"),
+            QuorumRule::Fixed(n) => write!(f, "fixed:{}", n),
+            QuorumRule::ShareOfSeats { num, den } => write!(f, "{}/{}", num, den),
+            QuorumRule::ShareOfPresent { num, den } => write!(f, "present:{}/{}", num, den),
+        }
+    }
+}
+
+impl FromStr for QuorumRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "half" => return Ok(QuorumRule::AtLeastHalf),
+            "more-than-half" => return Ok(QuorumRule::MoreThanHalf),
+            _ => {}
+        }
+        if let Some(n) = s.strip_prefix("fixed:") {
+            let n = n.parse().map_err(|_| format!("Ogiltigt antal i '{}'", s))?;
+            if n == 0 {
+                return Err(format!("Kvorum i '{}' måste vara minst 1", s));
+            }
+            return Ok(QuorumRule::Fixed(n));
+        }
+        if let Some(frac) = s.strip_prefix("present:") {
+            let (num, den) = parse_fraction(frac)?;
+            return Ok(QuorumRule::ShareOfPresent { num, den });
+        }
+        if s.contains('/') {
+            let (num, den) = parse_fraction(&s)?;
+            return Ok(QuorumRule::ShareOfSeats { num, den });
+        }
+        Err(format!(
+            "Okänd kvorumregel '{}' (half, more-than-half, fixed:N, A/B, present:A/B)",
+            s
+        ))
+    }
+}
+
+impl TryFrom<String> for QuorumRule {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<QuorumRule> for String {
+    fn from(rule: QuorumRule) -> Self {
+        rule.to_string()
+    }
+}
+
+fn parse_fraction(s: &str) -> Result<(usize, usize), String> {
+    let (num, den) = s
+        .split_once('/')
+        .ok_or_else(|| format!("Förväntade ett bråk A/B, fick '{}'", s))?;
+    let num: usize = num
+        .trim()
+        .parse()
+        .map_err(|_| format!("Ogiltig täljare i '{}'", s))?;
+    let den: usize = den
+        .trim()
+        .parse()
+        .map_err(|_| format!("Ogiltig nämnare i '{}'", s))?;
+    if num == 0 || num > den {
+        return Err(format!("Bråket '{}' måste vara större än 0 och högst 1", s));
+    }
+    Ok((num, den))
+}
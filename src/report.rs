@@ -0,0 +1,239 @@
+//! Analysresultatet som fristående data för export.
+//!
+//! `AnalysisResult` lånar styrelserna och känner bara identiteter. `Report`
+//! äger allt och har visningsnamn och närvaro ifyllda, så att det kan
+//! serialiseras direkt för skript och mallar.
+
+use crate::analyzer::{AnalysisResult, CallIn, CallInPlan, ConflictReason, SeatSummary};
+use crate::corrections::Correction;
+use crate::identity::PersonId;
+use crate::policy::Verdict;
+use crate::quorum::QuorumRule;
+use crate::types::Board;
+use crate::year::VerksamhetsAr;
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub voting_body: BoardRef,
+    /// Åren som de granskade styrelserna hör till, i tidsordning.
+    pub audited_years: Vec<VerksamhetsAr>,
+    pub total_seats: usize,
+    pub seats: SeatSummary,
+    pub present_count: usize,
+    /// Närvarande ordinarie plus ersättare för frånvarande och vakanta mandat.
+    pub seated: usize,
+    pub quorum_rule: QuorumRule,
+    /// `quorum_rule` i klartext.
+    pub quorum_description: String,
+    pub quorum_limit: usize,
+    /// Falskt om för få är röstberättigade för att mötet ska vara beslutsmässigt.
+    pub quorate: bool,
+    pub optimal: bool,
+    pub groups: Vec<GroupReport>,
+    /// Styrelser där för få röstberättigade återstår.
+    pub impossible: Vec<BoardReport>,
+    /// Vilka frånvarande som behöver ringas in för att rädda `impossible`,
+    /// se `Report::with_call_in`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_in: Option<CallInReport>,
+    /// Namnkorrigeringar som resultatet bygger på.
+    pub corrections: Vec<Correction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoardRef {
+    pub name: String,
+    pub year: VerksamhetsAr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PersonReport {
+    pub id: PersonId,
+    pub name: String,
+    pub present: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoardReport {
+    pub name: String,
+    pub year: VerksamhetsAr,
+    pub conflicts: Vec<PersonReport>,
+    /// Varje post och deklarerad relation som kopplar någon i det röstande
+    /// organet till styrelsen, med jävspolicyns utfall och regeln bakom det.
+    pub causes: Vec<CauseReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CauseReport {
+    pub person: PersonReport,
+    pub reason: ConflictReason,
+    pub verdict: Verdict,
+    /// `None` när policyns standardutfall gäller.
+    pub rule: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubstitutionReport {
+    pub deputy: PersonReport,
+    /// `None` när ersättaren går in på ett vakant mandat.
+    pub replaces: Option<PersonReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CallInReport {
+    pub boards: Vec<BoardCallInReport>,
+    /// Minsta mängd som räddar alla styrelserna på en gång.
+    pub all: CallIn<PersonReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoardCallInReport {
+    pub name: String,
+    pub year: VerksamhetsAr,
+    /// Minsta mängd att ringa in för just den här styrelsen.
+    pub call: CallIn<PersonReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GroupReport {
+    /// Åren som gruppens styrelser hör till, i tidsordning.
+    pub years: Vec<VerksamhetsAr>,
+    pub eligible: usize,
+    /// Röstberättigade utöver kvorum.
+    pub margin: usize,
+    /// Röstberättigade vars avgång ensam fäller kvorum i gruppen.
+    pub breaking: Vec<PersonReport>,
+    /// Jäviga i gruppen sorterade efter namn, närvarande såväl som frånvarande.
+    pub conflicted: Vec<PersonReport>,
+    pub substitutions: Vec<SubstitutionReport>,
+    /// Röstberättigade i gruppen, se `VotingGroup::voters`.
+    pub voters: Vec<PersonReport>,
+    /// Styrelserna sorterade efter år och namn.
+    pub boards: Vec<BoardReport>,
+}
+
+impl Report {
+    pub fn new(
+        analysis: &AnalysisResult,
+        voting_body: &Board,
+        absent: &HashSet<PersonId>,
+        corrections: &[Correction],
+    ) -> Self {
+        let person = |id: &PersonId| PersonReport {
+            id: id.clone(),
+            name: analysis.name_of(id).to_string(),
+            present: !absent.contains(id),
+        };
+        let board = |b: &Board| BoardReport {
+            name: b.name.clone(),
+            year: b.year,
+            conflicts: analysis.conflicts_of(b).iter().map(person).collect(),
+            causes: analysis
+                .causes_of(b)
+                .iter()
+                .map(|c| CauseReport {
+                    person: person(&c.person),
+                    reason: c.reason.clone(),
+                    verdict: c.verdict,
+                    rule: c.rule.clone(),
+                })
+                .collect(),
+        };
+        let sorted_boards = |boards: &[&Board]| {
+            let mut sorted = boards.to_vec();
+            sorted.sort_by(|a, b| a.year.cmp(&b.year).then_with(|| a.name.cmp(&b.name)));
+            sorted.into_iter().map(board).collect::<Vec<_>>()
+        };
+
+        let groups = analysis
+            .groups
+            .iter()
+            .map(|group| {
+                let mut conflicted: Vec<PersonReport> =
+                    group.conflicted_members.iter().map(person).collect();
+                conflicted.sort_by(|a, b| a.name.cmp(&b.name));
+                GroupReport {
+                    years: years_of(&group.boards),
+                    eligible: group.eligible,
+                    margin: group.margin,
+                    breaking: group.breaking.iter().map(person).collect(),
+                    conflicted,
+                    substitutions: group
+                        .substitutions
+                        .iter()
+                        .map(|s| SubstitutionReport {
+                            deputy: person(&s.deputy),
+                            replaces: s.replaces.as_ref().map(person),
+                        })
+                        .collect(),
+                    voters: group.voters.iter().map(person).collect(),
+                    boards: sorted_boards(&group.boards),
+                }
+            })
+            .collect();
+
+        let audited: Vec<&Board> = analysis
+            .groups
+            .iter()
+            .flat_map(|g| g.boards.iter().copied())
+            .chain(analysis.impossible.iter().copied())
+            .collect();
+
+        Report {
+            voting_body: BoardRef {
+                name: voting_body.name.clone(),
+                year: voting_body.year,
+            },
+            audited_years: years_of(&audited),
+            total_seats: analysis.total_seats,
+            seats: analysis.seats.clone(),
+            present_count: analysis.present_count,
+            seated: analysis.seated,
+            quorum_rule: analysis.quorum_rule,
+            quorum_description: analysis.quorum_rule.describe(),
+            quorum_limit: analysis.quorum_limit,
+            quorate: analysis.quorate(),
+            optimal: analysis.optimal,
+            groups,
+            impossible: sorted_boards(&analysis.impossible),
+            call_in: None,
+            corrections: corrections.to_vec(),
+        }
+    }
+
+    /// Lägger till förslag på vilka som ska ringas in, från `suggest_call_ins`
+    /// för samma analys.
+    pub fn with_call_in(mut self, plan: &CallInPlan, analysis: &AnalysisResult) -> Self {
+        let person = |id: &PersonId| PersonReport {
+            id: id.clone(),
+            name: analysis.name_of(id).to_string(),
+            present: false,
+        };
+        self.call_in = Some(CallInReport {
+            boards: plan
+                .boards
+                .iter()
+                .map(|b| BoardCallInReport {
+                    name: b.name.clone(),
+                    year: b.year,
+                    call: b.call.map(person),
+                })
+                .collect(),
+            all: plan.all.map(person),
+        });
+        self
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+fn years_of(boards: &[&Board]) -> Vec<VerksamhetsAr> {
+    let mut years: Vec<VerksamhetsAr> = boards.iter().map(|b| b.year).collect();
+    years.sort();
+    years.dedup();
+    years
+}
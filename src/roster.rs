@@ -0,0 +1,51 @@
+use crate::types::Board;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Serialiserbar ögonblicksbild av alla styrelser.
+///
+/// TOML kräver en tabell på toppnivå, därför ligger listan under `boards`
+/// (`[[boards]]` i TOML, `{"boards": [...]}` i JSON).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Roster {
+    pub boards: Vec<Board>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RosterFormat {
+    Json,
+    Toml,
+}
+
+impl RosterFormat {
+    /// Avgör formatet utifrån filändelsen, `None` om det inte är en roster-fil.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = Path::new(path).extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "json" => Some(RosterFormat::Json),
+            "toml" => Some(RosterFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse_roster(content: &str, format: RosterFormat) -> Result<Vec<Board>> {
+    let roster: Roster = match format {
+        RosterFormat::Json => serde_json::from_str(content).context("Ogiltig JSON-roster")?,
+        RosterFormat::Toml => toml::from_str(content).context("Ogiltig TOML-roster")?,
+    };
+    Ok(roster.boards)
+}
+
+pub fn render_roster(boards: &[Board], format: RosterFormat) -> Result<String> {
+    let mut boards = boards.to_vec();
+    boards.sort_by(|a, b| a.year.cmp(&b.year).then_with(|| a.name.cmp(&b.name)));
+    let roster = Roster { boards };
+
+    let text = match format {
+        RosterFormat::Json => serde_json::to_string_pretty(&roster)?,
+        RosterFormat::Toml => toml::to_string_pretty(&roster)?,
+    };
+    Ok(text)
+}
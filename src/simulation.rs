@@ -0,0 +1,288 @@
+//! Simulering av närvaron inför ett möte.
+//!
+//! Innan mötet vet man bara ungefär vilka som kommer. `simulate` drar många
+//! närvarouppsättningar ur sannolikheter per person och kör samma analys som
+//! mötet använder på var och en, så att man ser hur ofta varje styrelse blir
+//! omöjlig att behandla och hur många grupper som brukar behövas.
+//!
+//! Sannolikheterna läses från en TOML-fil med egna värden, tidigare möten
+//! eller båda:
+//!
+//! ```toml
+//! default = 0.9
+//!
+//! [probability]
+//! "Anna Berg" = 0.5
+//!
+//! [[meeting]]
+//! date = "2025-03-12"
+//! present = ["Anna Berg", "Cecilia Ek"]
+//! absent = ["David Lund"]
+//! ```
+
+use crate::analyzer::{AnalysisOptions, analyze_voting_groups_with};
+use crate::identity::{Directory, PersonId};
+use crate::quorum::QuorumRule;
+use crate::types::Board;
+use crate::year::VerksamhetsAr;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+
+/// Sannolikheten för den som varken har ett eget värde eller finns med i
+/// något tidigare möte, om filen inte anger `default`.
+pub const DEFAULT_ATTENDANCE: f64 = 0.9;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttendanceFile {
+    /// Sannolikheten för den som saknar andra uppgifter.
+    pub default: Option<f64>,
+    /// Sannolikhet per namn. Går före tidigare möten.
+    #[serde(default)]
+    pub probability: BTreeMap<String, f64>,
+    #[serde(default, rename = "meeting")]
+    pub meetings: Vec<PastMeeting>,
+}
+
+/// Ett tidigare möte. Ett möte räknas bara för de personer som står med i
+/// `present` eller `absent`, så att nyvalda inte får frånvaro från möten
+/// innan de valdes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PastMeeting {
+    pub date: Option<String>,
+    #[serde(default)]
+    pub present: Vec<String>,
+    #[serde(default)]
+    pub absent: Vec<String>,
+}
+
+/// En persons närvarosannolikhet och var den kommer ifrån.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Attendance {
+    pub id: PersonId,
+    pub name: String,
+    pub probability: f64,
+    pub source: AttendanceSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttendanceSource {
+    /// Angiven i `[probability]`.
+    Given,
+    /// Andelen tidigare möten personen var med på.
+    PastMeetings {
+        attended: usize,
+        listed: usize,
+    },
+    /// Känd frånvaro, t.ex. från `--absent`.
+    Absent,
+    Default,
+}
+
+impl AttendanceFile {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Kunde inte läsa närvarofilen {}", path))?;
+        Self::parse(&text).with_context(|| format!("Ogiltig närvarofil {}", path))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: AttendanceFile = toml::from_str(text)?;
+        let out_of_range = |p: f64| !(0.0..=1.0).contains(&p);
+        if let Some(p) = file.default
+            && out_of_range(p)
+        {
+            bail!("default = {} ligger inte mellan 0 och 1", p);
+        }
+        if let Some((name, p)) = file.probability.iter().find(|(_, p)| out_of_range(**p)) {
+            bail!(
+                "Sannolikheten {} för '{}' ligger inte mellan 0 och 1",
+                p,
+                name
+            );
+        }
+        Ok(file)
+    }
+
+    /// Sannolikheterna för alla ordinarie och ersättare i `fum`, i rosterns
+    /// ordning. `absent` har sannolikheten noll. Returnerar även namn i filen
+    /// som inte finns i rostern.
+    pub fn resolve(
+        &self,
+        fum: &Board,
+        absent: &[String],
+        directory: &Directory,
+    ) -> (Vec<Attendance>, Vec<String>) {
+        let id_of = |name: &str| directory.resolve(name, &fum.name);
+
+        let mut people: Vec<Attendance> = Vec::new();
+        for m in fum.people() {
+            let id = id_of(&m.name);
+            if people.iter().any(|p| p.id == id) {
+                continue;
+            }
+            let name = directory.display_name(&id).unwrap_or(&m.name).to_string();
+            people.push(Attendance {
+                id,
+                name,
+                probability: self.default.unwrap_or(DEFAULT_ATTENDANCE),
+                source: AttendanceSource::Default,
+            });
+        }
+
+        let mut unknown: Vec<String> = Vec::new();
+        let mut find = |name: &str| {
+            let found = directory.find_on(fum, name.trim()).map(|m| id_of(&m.name));
+            if found.is_none() && !unknown.iter().any(|u| u == name.trim()) {
+                unknown.push(name.trim().to_string());
+            }
+            found
+        };
+
+        let mut history: HashMap<PersonId, (usize, usize)> = HashMap::new();
+        for meeting in &self.meetings {
+            let listed = meeting
+                .present
+                .iter()
+                .map(|n| (n, true))
+                .chain(meeting.absent.iter().map(|n| (n, false)));
+            for (name, attended) in listed {
+                if let Some(id) = find(name) {
+                    let entry = history.entry(id).or_default();
+                    entry.0 += usize::from(attended);
+                    entry.1 += 1;
+                }
+            }
+        }
+        let given: HashMap<PersonId, f64> = self
+            .probability
+            .iter()
+            .filter_map(|(name, p)| find(name).map(|id| (id, *p)))
+            .collect();
+        let absent: HashSet<PersonId> = absent.iter().map(|n| id_of(n)).collect();
+
+        for person in &mut people {
+            if absent.contains(&person.id) {
+                person.probability = 0.0;
+                person.source = AttendanceSource::Absent;
+            } else if let Some(p) = given.get(&person.id) {
+                person.probability = *p;
+                person.source = AttendanceSource::Given;
+            } else if let Some(&(attended, listed)) = history.get(&person.id) {
+                person.probability = attended as f64 / listed as f64;
+                person.source = AttendanceSource::PastMeetings { attended, listed };
+            }
+        }
+        (people, unknown)
+    }
+}
+
+/// Resultatet av en simulering.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Simulation {
+    pub runs: usize,
+    /// Fröet som ger samma dragningar igen.
+    pub seed: u64,
+    pub quorum_rule: QuorumRule,
+    /// `quorum_rule` i klartext.
+    pub quorum_description: String,
+    pub attendance: Vec<Attendance>,
+    /// Andel körningar där mötet var beslutsmässigt.
+    pub quorate: f64,
+    /// Andel körningar där alla styrelser kunde behandlas.
+    pub all_decidable: f64,
+    /// Genomsnittligt antal röstningsgrupper i de beslutsmässiga körningarna,
+    /// eller `None` om ingen körning var beslutsmässig.
+    pub expected_groups: Option<f64>,
+    /// Styrelserna sorterade efter år och namn.
+    pub boards: Vec<BoardOdds>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BoardOdds {
+    pub name: String,
+    pub year: VerksamhetsAr,
+    /// Andel körningar där styrelsen inte kunde behandlas, inklusive de där
+    /// mötet inte var beslutsmässigt.
+    pub impossible: f64,
+}
+
+/// Kör analysen `runs` gånger med närvaro dragen ur `attendance`. Samma
+/// `seed` ger samma resultat.
+pub fn simulate(
+    fum: &Board,
+    boards: &[&Board],
+    attendance: &[Attendance],
+    runs: usize,
+    seed: u64,
+    options: &AnalysisOptions,
+) -> Simulation {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    let mut sorted = boards.to_vec();
+    sorted.sort_by(|a, b| a.year.cmp(&b.year).then_with(|| a.name.cmp(&b.name)));
+
+    let mut impossible = vec![0usize; sorted.len()];
+    let mut quorate = 0usize;
+    let mut all_decidable = 0usize;
+    let mut groups = 0usize;
+
+    for _ in 0..runs {
+        let absent: HashSet<PersonId> = attendance
+            .iter()
+            .filter(|a| rng.f64() >= a.probability)
+            .map(|a| a.id.clone())
+            .collect();
+        let analysis = analyze_voting_groups_with(fum, &sorted, &absent, options);
+        if !analysis.quorate() {
+            impossible.iter_mut().for_each(|n| *n += 1);
+            continue;
+        }
+        quorate += 1;
+        groups += analysis.groups.len();
+        all_decidable += usize::from(analysis.impossible.is_empty());
+        for board in &analysis.impossible {
+            let i = sorted
+                .iter()
+                .position(|b| b.name == board.name && b.year == board.year)
+                .expect("analysen returnerar bara givna styrelser");
+            impossible[i] += 1;
+        }
+    }
+
+    let share = |n: usize| {
+        if runs == 0 {
+            0.0
+        } else {
+            n as f64 / runs as f64
+        }
+    };
+    Simulation {
+        runs,
+        seed,
+        quorum_rule: options.quorum_rule,
+        quorum_description: options.quorum_rule.describe(),
+        attendance: attendance.to_vec(),
+        quorate: share(quorate),
+        all_decidable: share(all_decidable),
+        expected_groups: (quorate > 0).then(|| groups as f64 / quorate as f64),
+        boards: sorted
+            .iter()
+            .zip(impossible)
+            .map(|(b, n)| BoardOdds {
+                name: b.name.clone(),
+                year: b.year,
+                impossible: share(n),
+            })
+            .collect(),
+    }
+}
+
+impl Simulation {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
@@ -0,0 +1,67 @@
+//! Hämtning och parsning av en källa: URL, HTML-fil, textfil eller roster.
+
+use crate::error::Error;
+use crate::parser::{Diagnostic, ParsedBoards, parse_html_boards, parse_text_boards};
+use crate::roster::{RosterFormat, parse_roster};
+use crate::types::Board;
+use std::fs;
+use tracing::{Level, debug, info, trace, warn};
+
+/// Läser källan från en URL (`http...`) eller en fil.
+pub fn fetch_content(source: &str) -> Result<String, Error> {
+    let fetch_error = |reason: String| Error::Fetch {
+        location: source.to_string(),
+        reason,
+    };
+    if source.starts_with("http") {
+        info!("Laddar ner HTML från URL: {}", source);
+        reqwest::blocking::get(source)
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.text())
+            .map_err(|e| fetch_error(e.to_string()))
+    } else {
+        info!("Läser fil: {}", source);
+        fs::read_to_string(source).map_err(|e| fetch_error(e.to_string()))
+    }
+}
+
+/// Parsar innehållet efter källans filändelse: .json/.toml, .txt eller HTML.
+/// Parserns diagnostik loggas med `tracing` på respektive nivå.
+pub fn parse_source(source: &str, content: &str) -> Result<Vec<Board>, Error> {
+    let parsed = if let Some(format) = RosterFormat::from_path(source) {
+        info!("Läser roster ({:?})...", format);
+        parse_roster(content, format).map(|boards| ParsedBoards {
+            boards,
+            diagnostics: Vec::new(),
+        })
+    } else if source.to_lowercase().ends_with(".txt") {
+        info!("Parsar textinnehåll...");
+        parse_text_boards(content)
+    } else {
+        info!("Parsar HTML-innehåll...");
+        parse_html_boards(content)
+    };
+    let parsed = parsed.map_err(|e| Error::Parse {
+        location: source.to_string(),
+        reason: format!("{:#}", e),
+    })?;
+    for diagnostic in &parsed.diagnostics {
+        log_diagnostic(diagnostic);
+    }
+    Ok(parsed.boards)
+}
+
+fn log_diagnostic(diagnostic: &Diagnostic) {
+    match diagnostic.level() {
+        Level::ERROR | Level::WARN => warn!("{}", diagnostic),
+        Level::INFO => info!("{}", diagnostic),
+        Level::DEBUG => debug!("{}", diagnostic),
+        Level::TRACE => trace!("{}", diagnostic),
+    }
+}
+
+pub fn load_boards(source: &str) -> Result<Vec<Board>, Error> {
+    let boards = parse_source(source, &fetch_content(source)?)?;
+    info!("Hittade {} styrelser totalt i {}.", boards.len(), source);
+    Ok(boards)
+}
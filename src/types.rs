@@ -1,12 +1,63 @@
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+use crate::year::VerksamhetsAr;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Member {
     pub position: String,
     pub name: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl Member {
+    /// Namnet som används för en obesatt post.
+    pub const VACANT: &'static str = "Vakant";
+
+    /// Sant för ersättare och suppleanter.
+    pub fn is_deputy(&self) -> bool {
+        let position = self.position.to_lowercase();
+        position.contains("ersättare") || position.contains("suppleant")
+    }
+
+    /// Sant om posten är obesatt ("Vakant", "(vakans)", "-" eller tomt namn).
+    pub fn is_vacant(&self) -> bool {
+        is_vacant_name(&self.name)
+    }
+}
+
+pub fn is_vacant_name(name: &str) -> bool {
+    let cleaned = name
+        .trim()
+        .trim_matches(|c| c == '(' || c == ')')
+        .trim()
+        .to_lowercase();
+    cleaned.is_empty() || cleaned == "-" || cleaned.starts_with("vakan")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Board {
     pub name: String,
-    pub year: String,
+    pub year: VerksamhetsAr,
     pub members: Vec<Member>,
+    /// Antal mandat enligt stadgarna, om det skiljer sig från rostern.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seats: Option<usize>,
+    /// Ersättare i den ordning de går in för ordinarie ledamöter.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deputies: Vec<Member>,
+}
+
+impl Board {
+    /// Ledamöter som faktiskt innehar en post.
+    pub fn seated_members(&self) -> impl Iterator<Item = &Member> {
+        self.members.iter().filter(|m| !m.is_vacant())
+    }
+
+    /// Ersättare som faktiskt är valda, i prioritetsordning.
+    pub fn seated_deputies(&self) -> impl Iterator<Item = &Member> {
+        self.deputies.iter().filter(|m| !m.is_vacant())
+    }
+
+    /// Alla personer knutna till styrelsen, ordinarie och ersättare.
+    pub fn people(&self) -> impl Iterator<Item = &Member> {
+        self.seated_members().chain(self.seated_deputies())
+    }
 }
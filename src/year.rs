@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Ett verksamhetsår, antingen brutet (juli–juni) eller ett kalenderår.
+///
+/// Textformen (se `FromStr`) används i källorna, på kommandoraden och i
+/// rostrar och korrigeringsfiler:
+///
+/// | Text              | År                                        |
+/// |-------------------|-------------------------------------------|
+/// | `2024/2025`       | brutet verksamhetsår                      |
+/// | `24/25`           | samma, med tvåsiffriga årtal              |
+/// | `2425`            | samma, förkortat utan snedstreck          |
+/// | `2025`            | kalenderår                                |
+/// | `kalenderår 2021` | kalenderår som annars vore en förkortning |
+/// | `Okänt år`        | årtal saknas i källan                     |
+///
+/// Fyra siffror tolkas som förkortning när de två sista är ett mer än de
+/// två första, så "2021" blir 2020/2021 och inte kalenderåret 2021. Sådana
+/// kalenderår skrivs därför ut som `kalenderår 2021`.
+///
+/// Åren sorteras efter när de börjar: `Okänt år` först, sedan 2024/2025
+/// (juli 2024) före kalenderåret 2025 (januari 2025) före 2025/2026.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum VerksamhetsAr {
+    Unknown,
+    Split { start: u16 },
+    Calendar(u16),
+}
+
+impl VerksamhetsAr {
+    /// Texten för ett okänt år.
+    pub const UNKNOWN: &'static str = "Okänt år";
+
+    /// Prefixet för kalenderår som annars skulle läsas som en förkortning.
+    pub const CALENDAR: &'static str = "kalenderår";
+
+    /// Brutet verksamhetsår som börjar hösten `start`.
+    pub fn split(start: u16) -> Self {
+        VerksamhetsAr::Split { start }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        *self == VerksamhetsAr::Unknown
+    }
+
+    /// Året innan, t.ex. 2024/2025 för 2025/2026. `None` för ett okänt år.
+    pub fn previous(&self) -> Option<Self> {
+        match *self {
+            VerksamhetsAr::Unknown => None,
+            VerksamhetsAr::Split { start } => start.checked_sub(1).map(VerksamhetsAr::split),
+            VerksamhetsAr::Calendar(year) => year.checked_sub(1).map(VerksamhetsAr::Calendar),
+        }
+    }
+
+    /// Året efter, t.ex. 2026/2027 för 2025/2026. `None` för ett okänt år.
+    pub fn next(&self) -> Option<Self> {
+        match *self {
+            VerksamhetsAr::Unknown => None,
+            VerksamhetsAr::Split { start } => start.checked_add(1).map(VerksamhetsAr::split),
+            VerksamhetsAr::Calendar(year) => year.checked_add(1).map(VerksamhetsAr::Calendar),
+        }
+    }
+
+    /// Sorteringsnyckel i halvår från år 0, så att brutna år hamnar mitt i
+    /// kalenderåret de börjar.
+    fn sort_key(&self) -> Option<u32> {
+        match *self {
+            VerksamhetsAr::Unknown => None,
+            VerksamhetsAr::Split { start } => Some(u32::from(start) * 2 + 1),
+            VerksamhetsAr::Calendar(year) => Some(u32::from(year) * 2),
+        }
+    }
+}
+
+impl Ord for VerksamhetsAr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl PartialOrd for VerksamhetsAr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for VerksamhetsAr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            VerksamhetsAr::Unknown => f.pad(Self::UNKNOWN),
+            VerksamhetsAr::Split { start } => f.pad(&format!("{}/{}", start, u32::from(start) + 1)),
+            VerksamhetsAr::Calendar(year) => {
+                let plain = year.to_string();
+                if plain.parse() == Ok(*self) {
+                    f.pad(&plain)
+                } else {
+                    f.pad(&format!("{} {}", Self::CALENDAR, year))
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for VerksamhetsAr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || {
+            format!(
+                "Ogiltigt verksamhetsår '{}' (2024/2025, 24/25, 2425, 2025, '{} 2021' eller '{}')",
+                s,
+                Self::CALENDAR,
+                Self::UNKNOWN
+            )
+        };
+        if s.to_lowercase() == Self::UNKNOWN.to_lowercase() {
+            return Ok(VerksamhetsAr::Unknown);
+        }
+        if let Some(year) = s.to_lowercase().strip_prefix(Self::CALENDAR) {
+            let year = year.trim();
+            if year.len() != 4 || !is_digits(year) {
+                return Err(invalid());
+            }
+            return Ok(VerksamhetsAr::Calendar(
+                year.parse().map_err(|_| invalid())?,
+            ));
+        }
+
+        let (start, end) = match s.split_once('/') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None if s.len() == 4 && is_digits(s) => {
+                let (start, end) = s.split_at(2);
+                if end.parse::<u16>() == start.parse::<u16>().map(|n| n + 1) {
+                    (start, end)
+                } else {
+                    return Ok(VerksamhetsAr::Calendar(s.parse().map_err(|_| invalid())?));
+                }
+            }
+            None => return Err(invalid()),
+        };
+        let start = expand_year(start).ok_or_else(invalid)?;
+        let end = expand_year(end).ok_or_else(invalid)?;
+        if end != start + 1 {
+            return Err(format!(
+                "Verksamhetsåret '{}' måste sträcka sig över två på varandra följande år",
+                s
+            ));
+        }
+        Ok(VerksamhetsAr::split(start))
+    }
+}
+
+impl TryFrom<String> for VerksamhetsAr {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<VerksamhetsAr> for String {
+    fn from(year: VerksamhetsAr) -> Self {
+        year.to_string()
+    }
+}
+
+/// Ett eller flera på varandra följande verksamhetsår, t.ex. för
+/// `--audit-years`. Textformen är ett år eller ett intervall
+/// `2022/2023..2024/2025` där båda ändpunkterna ingår.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YearRange {
+    pub first: VerksamhetsAr,
+    pub last: VerksamhetsAr,
+}
+
+impl YearRange {
+    /// Alla år i intervallet i tidsordning.
+    pub fn years(&self) -> Vec<VerksamhetsAr> {
+        let mut years = vec![self.first];
+        while let Some(next) = years.last().and_then(|y| y.next())
+            && next <= self.last
+        {
+            years.push(next);
+        }
+        years
+    }
+}
+
+impl FromStr for YearRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, last) = match s.split_once("..") {
+            Some((first, last)) => (first.parse()?, last.parse()?),
+            None => {
+                let year: VerksamhetsAr = s.parse()?;
+                (year, year)
+            }
+        };
+        match (first, last) {
+            (VerksamhetsAr::Unknown, _) | (_, VerksamhetsAr::Unknown) => Err(format!(
+                "Intervallet '{}' kan inte innehålla ett okänt år",
+                s
+            )),
+            (VerksamhetsAr::Split { .. }, VerksamhetsAr::Calendar(_))
+            | (VerksamhetsAr::Calendar(_), VerksamhetsAr::Split { .. }) => Err(format!(
+                "Intervallet '{}' blandar brutna år och kalenderår",
+                s
+            )),
+            _ if first > last => Err(format!("Intervallet '{}' slutar före det börjar", s)),
+            _ => Ok(YearRange { first, last }),
+        }
+    }
+}
+
+/// "2023/2024, 2024/2025"
+pub fn join_years(years: &[VerksamhetsAr]) -> String {
+    let years: Vec<String> = years.iter().map(ToString::to_string).collect();
+    years.join(", ")
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+/// "2024" eller "24" (2000-talet).
+fn expand_year(s: &str) -> Option<u16> {
+    if !is_digits(s) {
+        return None;
+    }
+    match s.len() {
+        2 => s.parse::<u16>().ok().map(|n| 2000 + n),
+        4 => s.parse().ok(),
+        _ => None,
+    }
+}
@@ -1,12 +1,35 @@
-use containtment_check::analyzer::analyze_voting_groups;
-use containtment_check::parser::parse_html_boards;
+use containtment_check::analyzer::{
+    AnalysisOptions, CallIn, ConflictReason, analyze_voting_groups, analyze_voting_groups_with,
+    suggest_call_ins,
+};
+use containtment_check::audit::Audit;
+use containtment_check::corrections::{
+    Correction, Decision, DecisionLog, apply_corrections, find_suggestions,
+};
+use containtment_check::declarations::Declarations;
+use containtment_check::diff::diff_rosters;
+use containtment_check::dot::render_dot;
+use containtment_check::error::Error;
+use containtment_check::history::{find_people, people_history};
+use containtment_check::identity::{Directory, PersonId};
+use containtment_check::meeting::{Involvement, Meeting, MeetingCommand};
+use containtment_check::names::NameMatcher;
+use containtment_check::parser::{DiagnosticKind, parse_html_boards, parse_text_boards};
+use containtment_check::policy::{ConflictPolicy, Verdict};
+use containtment_check::protocol::ProtocolTemplate;
+use containtment_check::quorum::QuorumRule;
+use containtment_check::report::Report;
+use containtment_check::roster::{RosterFormat, parse_roster, render_roster};
+use containtment_check::simulation::{AttendanceFile, AttendanceSource, simulate};
+use containtment_check::source::{load_boards, parse_source};
 use containtment_check::types::{Board, Member};
+use containtment_check::year::{VerksamhetsAr, YearRange};
 use std::collections::HashSet;
 
 fn make_board(name: &str, year: &str, member_names: Vec<&str>) -> Board {
     Board {
         name: name.to_string(),
-        year: year.to_string(),
+        year: year.parse().unwrap(),
         members: member_names
             .into_iter()
             .map(|n| Member {
@@ -14,6 +37,8 @@ fn make_board(name: &str, year: &str, member_names: Vec<&str>) -> Board {
                 name: n.to_string(),
             })
             .collect(),
+        seats: None,
+        deputies: Vec::new(),
     }
 }
 
@@ -53,7 +78,7 @@ fn test_voting_logic_absence_causes_failure() {
 
     let all = vec![&fum, &b1];
     let mut absent = HashSet::new();
-    absent.insert("P3".to_string());
+    absent.insert(PersonId::from_name("P3"));
 
     let res = analyze_voting_groups(&fum, &all, &absent);
 
@@ -77,7 +102,7 @@ fn test_voting_logic_conflicted_person_is_absent() {
 
     let all = vec![&fum, &b1];
     let mut absent = HashSet::new();
-    absent.insert("P1".to_string()); // En av de jäviga är borta
+    absent.insert(PersonId::from_name("P1")); // En av de jäviga är borta
 
     let res = analyze_voting_groups(&fum, &all, &absent);
 
@@ -131,10 +156,1325 @@ fn test_voting_logic_grouping_split_by_absence() {
     // B2 ensam: Närvarande 4. Jäv {B}. Röst 3. OK.
     // B1+B2:    Närvarande 4. Jäv {A,B}. Röst 2. FAIL.
     let mut absent = HashSet::new();
-    absent.insert("C".to_string());
+    absent.insert(PersonId::from_name("C"));
     let res2 = analyze_voting_groups(&fum, &all, &absent);
 
     assert_eq!(res2.groups.len(), 2);
 }
 
-// ... (behåll parsing-testerna om du vill, men de behöver också uppdateras för att använda den nya funktionen som kräver wrapper)
+#[test]
+fn test_voting_logic_partition_is_minimal_not_first_fit() {
+    // FUM: 5 pers. Kvorum 3, alltså får högst 2 närvarande vara jäviga per grupp.
+    // First-fit: {B1,B2} = {A,B}, sedan får B3 {A,C} och B4 {B,D} egna grupper (3 st).
+    // Optimalt: {B1,B3} = {A,C} och {B2,B4} = {B,D} (2 st).
+    let fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D", "E"]);
+    let b1 = make_board("B1", "23/24", vec!["A"]);
+    let b2 = make_board("B2", "23/24", vec!["B"]);
+    let b3 = make_board("B3", "23/24", vec!["A", "C"]);
+    let b4 = make_board("B4", "23/24", vec!["B", "D"]);
+    let all = vec![&b1, &b2, &b3, &b4];
+
+    let res = analyze_voting_groups(&fum, &all, &HashSet::new());
+
+    assert!(res.optimal);
+    assert_eq!(res.groups.len(), 2);
+    let first: Vec<_> = res.groups[0]
+        .boards
+        .iter()
+        .map(|b| b.name.as_str())
+        .collect();
+    assert_eq!(first, vec!["B1", "B3"]);
+}
+
+#[test]
+fn test_quorum_rules_parse_and_limit() {
+    let cases = [
+        ("half", 21, 21, 11),
+        ("more-than-half", 20, 20, 11),
+        ("fixed:7", 21, 15, 7),
+        ("2/3", 21, 21, 14),
+        ("present:1/2", 21, 15, 8),
+    ];
+    for (text, seats, present, expected) in cases {
+        let rule: QuorumRule = text.parse().unwrap();
+        assert_eq!(rule.limit(seats, present), expected, "{}", text);
+        assert_eq!(rule.to_string(), text);
+    }
+    assert!("3/2".parse::<QuorumRule>().is_err());
+    assert!("nästan alla".parse::<QuorumRule>().is_err());
+    assert!("fixed:0".parse::<QuorumRule>().is_err());
+    assert!("present:0/2".parse::<QuorumRule>().is_err());
+}
+
+#[test]
+fn test_quorum_of_present_is_never_zero() {
+    // Är alla borta blir "1/2 av de närvarande" ändå minst en röst.
+    let fum = make_board("FUM", "24/25", vec!["A", "B", "C"]);
+    let b1 = make_board("StyrelseA", "23/24", vec!["A"]);
+    let all = vec![&b1];
+    let absent: HashSet<PersonId> = ["A", "B", "C"].map(PersonId::from_name).into();
+    let options = AnalysisOptions {
+        quorum_rule: "present:1/2".parse().unwrap(),
+        ..AnalysisOptions::default()
+    };
+
+    let res = analyze_voting_groups_with(&fum, &all, &absent, &options);
+    assert_eq!(res.quorum_limit, 1);
+    assert!(!res.quorate());
+    assert!(res.groups.is_empty());
+    assert_eq!(res.impossible.len(), 1);
+}
+
+#[test]
+fn test_voting_logic_more_than_half_rule() {
+    // FUM: 4 pers. "Minst hälften" ger kvorum 2, "mer än hälften" ger 3.
+    // Styrelse A har 2 jäviga, så bara 2 röstberättigade kvar.
+    let fum = make_board("FUM", "24/25", vec!["P1", "P2", "P3", "P4"]);
+    let b1 = make_board("StyrelseA", "23/24", vec!["P1", "P2"]);
+    let all = vec![&b1];
+
+    let res = analyze_voting_groups(&fum, &all, &HashSet::new());
+    assert_eq!(res.quorum_limit, 2);
+    assert_eq!(res.groups.len(), 1);
+
+    let options = AnalysisOptions {
+        quorum_rule: QuorumRule::MoreThanHalf,
+        ..AnalysisOptions::default()
+    };
+    let res = analyze_voting_groups_with(&fum, &all, &HashSet::new(), &options);
+    assert_eq!(res.quorum_limit, 3);
+    assert_eq!(res.quorum_rule, QuorumRule::MoreThanHalf);
+    assert_eq!(res.impossible.len(), 1);
+}
+
+#[test]
+fn test_voting_logic_vacancies_and_declared_seats() {
+    // FUM: 6 mandat varav 2 vakanta. Kvorum räknas mot 6 mandat = 3.
+    // Vakanta poster får inte matcha "Vakant" i andra styrelser.
+    let fum = make_board(
+        "FUM",
+        "24/25",
+        vec!["P1", "P2", "P3", "P4", "Vakant", "(vakant)"],
+    );
+    let b1 = make_board("StyrelseA", "23/24", vec!["P1", "Vakant"]);
+    let all = vec![&b1];
+
+    let res = analyze_voting_groups(&fum, &all, &HashSet::new());
+    assert_eq!(res.total_seats, 6);
+    assert_eq!(res.seats.vacant, 2);
+    assert_eq!(res.present_count, 4);
+    assert_eq!(res.quorum_limit, 3);
+    assert_eq!(res.groups.len(), 1);
+    assert_eq!(res.groups[0].conflicted_members.len(), 1);
+    assert!(!res.seats.mismatch());
+
+    // Stadgarna anger 9 mandat: kvorum 5 och bara 3 röstberättigade kvar.
+    let mut declared = fum.clone();
+    declared.seats = Some(9);
+    let res = analyze_voting_groups(&declared, &all, &HashSet::new());
+    assert_eq!(res.total_seats, 9);
+    assert!(res.seats.mismatch());
+    assert_eq!(res.impossible.len(), 1);
+}
+
+#[test]
+fn test_voting_logic_deputies_fill_absent_and_conflicted_seats() {
+    // FUM: 4 ordinarie (kvorum 2) och ersättarna E, F i den ordningen.
+    // B är frånvarande och A, C är jäviga. Utan ersättare återstår bara D.
+    // E går in för B och F för A, vilket ger 3 röstberättigade.
+    let mut fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D"]);
+    fum.deputies = make_board("", "Okänt år", vec!["E", "F"]).members;
+    let b1 = make_board("StyrelseA", "23/24", vec!["A", "C"]);
+    let all = vec![&b1];
+
+    let mut absent = HashSet::new();
+    absent.insert(PersonId::from_name("B"));
+    let res = analyze_voting_groups(&fum, &all, &absent);
+
+    assert_eq!(res.groups.len(), 1);
+    let group = &res.groups[0];
+    assert_eq!(group.eligible, 3);
+    let subs: Vec<_> = group
+        .substitutions
+        .iter()
+        .map(|s| {
+            (
+                res.name_of(&s.deputy),
+                s.replaces.as_ref().map(|r| res.name_of(r)),
+            )
+        })
+        .collect();
+    assert_eq!(subs, vec![("E", Some("B")), ("F", Some("A"))]);
+
+    // En jävig ersättare kan inte gå in.
+    let b2 = make_board("StyrelseB", "23/24", vec!["A", "C", "E"]);
+    let all = vec![&b2];
+    let res = analyze_voting_groups(&fum, &all, &absent);
+    assert_eq!(res.groups[0].eligible, 2);
+    assert_eq!(res.name_of(&res.groups[0].substitutions[0].deputy), "F");
+}
+
+#[test]
+fn test_quorum_is_rescued_by_deputies() {
+    // 6 mandat (kvorum 3), bara A och B närvarande men tre ersättare på plats.
+    let mut fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D", "E", "F"]);
+    fum.deputies = make_board("", "Okänt år", vec!["G", "H", "I"]).members;
+    let b1 = make_board("StyrelseA", "23/24", vec!["A"]);
+    let all = vec![&b1];
+
+    let absent: HashSet<PersonId> = ["C", "D", "E", "F"]
+        .into_iter()
+        .map(PersonId::from_name)
+        .collect();
+    let res = analyze_voting_groups(&fum, &all, &absent);
+    assert_eq!(res.present_count, 2);
+    assert_eq!(res.seated, 5);
+    assert!(res.quorate());
+
+    let report = Report::new(&res, &fum, &absent, &[]);
+    assert!(report.quorate);
+    assert_eq!(report.groups.len(), 1);
+
+    // Utan ersättarna är mötet inte beslutsmässigt.
+    fum.deputies.clear();
+    let res = analyze_voting_groups(&fum, &all, &absent);
+    assert!(!res.quorate());
+    assert!(!Report::new(&res, &fum, &absent, &[]).quorate);
+}
+
+#[test]
+fn test_deputies_fill_vacant_seats() {
+    // 6 mandat (kvorum 3) varav 4 vakanta. A och B är på plats och de två
+    // ersättarna går in på vakanta mandat.
+    let mut fum = make_board(
+        "FUM",
+        "24/25",
+        vec!["A", "B", "Vakant", "Vakant", "Vakant", "Vakant"],
+    );
+    fum.deputies = make_board("", "Okänt år", vec!["E", "F"]).members;
+    let b1 = make_board("StyrelseA", "23/24", vec!["A"]);
+    let all = vec![&b1];
+
+    let res = analyze_voting_groups(&fum, &all, &HashSet::new());
+    assert_eq!(res.quorum_limit, 3);
+    assert_eq!(res.seated, 4);
+    assert!(res.quorate());
+    assert!(res.impossible.is_empty());
+
+    let group = &res.groups[0];
+    assert_eq!(group.eligible, 3);
+    assert_eq!(group.voters.len(), 3);
+    assert!(group.substitutions.iter().all(|s| s.replaces.is_none()));
+}
+
+#[test]
+fn test_voting_groups_report_margin_and_breaking_absences() {
+    // FUM: A, B, C (kvorum 2). A är jävig, så B och C röstar utan marginal
+    // och ingen av dem kan gå. Med ersättaren D går D in för A, och om
+    // B eller C sedan går tar D över deras plats.
+    let mut fum = make_board("FUM", "24/25", vec!["A", "B", "C"]);
+    let x = make_board("X", "23/24", vec!["A"]);
+    let all = vec![&x];
+    let res = analyze_voting_groups(&fum, &all, &HashSet::new());
+    let group = &res.groups[0];
+    assert_eq!(group.margin, 0);
+    let breaking: Vec<&str> = group.breaking.iter().map(|id| res.name_of(id)).collect();
+    assert_eq!(breaking, ["B", "C"]);
+
+    fum.deputies = make_board("", "Okänt år", vec!["D"]).members;
+    let res = analyze_voting_groups(&fum, &all, &HashSet::new());
+    assert_eq!(res.groups[0].eligible, 3);
+    assert_eq!(res.groups[0].margin, 1);
+    assert!(res.groups[0].breaking.is_empty());
+}
+
+#[test]
+fn test_min_margin_prefers_groups_with_spare_voters() {
+    // FUM: A–F (kvorum 3). X och Y i samma grupp ger 4 röstberättigade,
+    // en över kvorum. Med krav på två extra behandlas de var för sig.
+    let fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D", "E", "F"]);
+    let x = make_board("X", "23/24", vec!["A"]);
+    let y = make_board("Y", "23/24", vec!["B"]);
+    let all = vec![&x, &y];
+
+    let res = analyze_voting_groups(&fum, &all, &HashSet::new());
+    assert_eq!(res.quorum_limit, 3);
+    assert_eq!(res.groups.len(), 1);
+    assert_eq!(res.groups[0].margin, 1);
+
+    let options = AnalysisOptions {
+        min_margin: 2,
+        ..AnalysisOptions::default()
+    };
+    let res = analyze_voting_groups_with(&fum, &all, &HashSet::new(), &options);
+    assert_eq!(res.groups.len(), 2);
+    assert!(res.groups.iter().all(|g| g.margin == 2));
+
+    // Ett krav som ingen styrelse når ensam ger inte sämre grupper än så.
+    let options = AnalysisOptions {
+        min_margin: 5,
+        ..AnalysisOptions::default()
+    };
+    let res = analyze_voting_groups_with(&fum, &all, &HashSet::new(), &options);
+    assert_eq!(res.groups.len(), 2);
+    assert!(res.impossible.is_empty());
+}
+
+#[test]
+fn test_simulation_attendance_sources_and_board_risk() {
+    let fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D"]);
+    let x = make_board("X", "23/24", vec!["A"]);
+    let y = make_board("Y", "23/24", vec!["A", "B"]);
+    let boards = vec![&x, &y];
+
+    // C har aldrig kommit och D anmäler frånvaro. Kvar blir A och B, så
+    // mötet är beslutsmässigt men ingen styrelse kan behandlas.
+    let file = AttendanceFile::parse(
+        r#"
+        default = 1.0
+        [probability]
+        B = 0.5
+        "Okänd" = 0.1
+        [[meeting]]
+        absent = ["C"]
+        "#,
+    )
+    .unwrap();
+    let (people, unknown) = file.resolve(&fum, &["D".to_string()], &Directory::default());
+    assert_eq!(unknown, ["Okänd"]);
+    let sources: Vec<_> = people.iter().map(|p| (p.name.as_str(), p.source)).collect();
+    assert_eq!(
+        sources,
+        [
+            ("A", AttendanceSource::Default),
+            ("B", AttendanceSource::Given),
+            (
+                "C",
+                AttendanceSource::PastMeetings {
+                    attended: 0,
+                    listed: 1
+                }
+            ),
+            ("D", AttendanceSource::Absent),
+        ]
+    );
+
+    let options = AnalysisOptions::default();
+    let sim = simulate(&fum, &boards, &people, 200, 42, &options);
+    assert_eq!(sim.quorum_rule, options.quorum_rule);
+    assert_eq!(sim.quorum_description, options.quorum_rule.describe());
+    assert_eq!(sim.boards[0].name, "X");
+    assert_eq!(sim.boards[0].impossible, 1.0);
+    assert_eq!(sim.boards[1].impossible, 1.0);
+    assert_eq!(sim.all_decidable, 0.0);
+    // Utan B är bara A kvar och mötet är inte beslutsmässigt.
+    assert!(sim.quorate > 0.3 && sim.quorate < 0.7);
+    assert_eq!(sim.expected_groups, Some(0.0));
+    assert_eq!(sim, simulate(&fum, &boards, &people, 200, 42, &options));
+
+    assert!(AttendanceFile::parse("default = 1.5").is_err());
+}
+
+#[test]
+fn test_conflict_policy_by_position_and_rule_in_report() {
+    let policy = ConflictPolicy::parse(
+        r#"
+        [[rule]]
+        position = " revisor "
+        verdict = "no-conflict"
+
+        [[rule]]
+        name = "Adjungerade prövas"
+        pattern = "(?i)^adjungerad"
+        verdict = "warn"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(policy.judge("Revisor").verdict, Verdict::NoConflict);
+    assert_eq!(policy.judge("Adjungerad ledamot").verdict, Verdict::Warn);
+    assert!(policy.judge("Ordförande").rule.is_none());
+    assert!(ConflictPolicy::parse("[[rule]]\nverdict = \"warn\"").is_err());
+    assert!(ConflictPolicy::parse("default = \"maybe\"").is_err());
+
+    let fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D"]);
+    let mut x = make_board("X", "23/24", vec!["A", "B", "C"]);
+    x.members[1].position = "Revisor".to_string();
+    x.members[2].position = "Adjungerad".to_string();
+    let all = vec![&x];
+    let options = AnalysisOptions {
+        policy,
+        ..AnalysisOptions::default()
+    };
+    let res = analyze_voting_groups_with(&fum, &all, &HashSet::new(), &options);
+    assert_eq!(res.conflicts_of(&x), &[PersonId::from_name("A")]);
+    assert_eq!(res.groups[0].eligible, 3);
+
+    let report = Report::new(&res, &fum, &HashSet::new(), &[]);
+    let causes: Vec<_> = report.groups[0].boards[0]
+        .causes
+        .iter()
+        .map(|c| (c.person.name.as_str(), c.verdict, c.rule.as_deref()))
+        .collect();
+    assert_eq!(
+        causes,
+        [
+            ("A", Verdict::Conflict, None),
+            ("B", Verdict::NoConflict, Some("post \"revisor\"")),
+            ("C", Verdict::Warn, Some("Adjungerade prövas")),
+        ]
+    );
+}
+
+#[test]
+fn test_declared_relationships_add_conflicts_with_reason() {
+    let declarations = Declarations::parse(
+        r#"
+        [[declaration]]
+        member = "D"
+        person = "Q"
+        reason = "Sambo"
+
+        [[declaration]]
+        member = "C"
+        board = "y"
+        year = "2023/2024"
+        reason = "Anställd av kåren"
+
+        [[declaration]]
+        member = "Okänd"
+        board = "Y"
+        reason = "Syskon"
+        "#,
+    )
+    .unwrap();
+    assert!(Declarations::parse("[[declaration]]\nmember = \"A\"\nreason = \"?\"").is_err());
+
+    let fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D", "E", "F"]);
+    assert_eq!(
+        declarations.unknown_members(&fum, &Directory::default()),
+        ["Okänd"]
+    );
+    let x = make_board("X", "23/24", vec!["A", "Q"]);
+    let y = make_board("Y", "23/24", vec!["B"]);
+    let older_y = make_board("Y", "22/23", vec!["B"]);
+    let all = vec![&x, &y, &older_y];
+    let options = AnalysisOptions {
+        declarations,
+        ..AnalysisOptions::default()
+    };
+    let res = analyze_voting_groups_with(&fum, &all, &HashSet::new(), &options);
+    let ids = |names: &[&str]| {
+        names
+            .iter()
+            .map(|n| PersonId::from_name(n))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(res.conflicts_of(&x), ids(&["A", "D"]));
+    assert_eq!(res.conflicts_of(&y), ids(&["B", "C"]));
+    assert_eq!(res.conflicts_of(&older_y), ids(&["B"]));
+
+    let report = Report::new(&res, &fum, &HashSet::new(), &[]);
+    let x_report = report
+        .groups
+        .iter()
+        .flat_map(|g| &g.boards)
+        .find(|b| b.name == "X")
+        .unwrap();
+    let reasons: Vec<String> = x_report
+        .causes
+        .iter()
+        .map(|c| c.reason.describe())
+        .collect();
+    assert_eq!(reasons, ["Ledamot", "Sambo: Q, Ledamot"]);
+    assert!(matches!(
+        x_report.causes[1].reason,
+        ConflictReason::Declared { .. }
+    ));
+
+    let protocol = ProtocolTemplate::default().render(&report);
+    assert!(protocol.contains("- D (Sambo: Q, Ledamot)"));
+}
+
+#[test]
+fn test_voting_logic_alias_file_identities() {
+    // "Eric Nilsson" på KIDS är samma person som FUM-ledamoten Erik Nilsson,
+    // medan Anna Berg på KPS är en annan person än FUM-ledamoten med samma namn.
+    let directory = Directory::parse(
+        r#"
+        [[person]]
+        id = "erik"
+        name = "Erik Nilsson"
+        aliases = ["Eric Nilsson"]
+
+        [[person]]
+        id = "anna-kps"
+        name = "Anna Berg"
+        boards = ["KPS"]
+        "#,
+    )
+    .unwrap();
+    let fum = make_board(
+        "FUM",
+        "24/25",
+        vec!["Erik Nilsson", "Anna Berg", "P3", "P4"],
+    );
+    let kids = make_board("KIDS", "23/24", vec!["Eric Nilsson"]);
+    let kps = make_board("KPS", "23/24", vec!["Anna Berg"]);
+    let all = vec![&kids, &kps];
+
+    let options = AnalysisOptions {
+        directory,
+        ..AnalysisOptions::default()
+    };
+    let res = analyze_voting_groups_with(&fum, &all, &HashSet::new(), &options);
+
+    assert_eq!(res.conflicts_of(&kids), &[PersonId::new("erik")]);
+    assert_eq!(res.name_of(&PersonId::new("erik")), "Erik Nilsson");
+    assert!(res.conflicts_of(&kps).is_empty());
+}
+
+#[test]
+fn test_absent_names_are_resolved_through_alias_file() {
+    let directory = Directory::parse(
+        r#"
+        [[person]]
+        id = "a1"
+        name = "A1"
+        aliases = ["Alpha Person"]
+        "#,
+    )
+    .unwrap();
+    let options = AnalysisOptions {
+        directory: directory.clone(),
+        ..AnalysisOptions::default()
+    };
+    let boards = vec![
+        make_board("Fullmäktige", "2025/2026", vec!["A1", "B", "C", "D"]),
+        make_board("KIDS", "2024/2025", vec!["B"]),
+    ];
+
+    let result = Audit::new(boards)
+        .absent(["Alpha Person"])
+        .options(options.clone())
+        .run()
+        .unwrap();
+    assert_eq!(result.absent, vec!["A1"]);
+    assert!(result.unknown_absent.is_empty());
+
+    let attendance = AttendanceFile::parse("[probability]\n\"Alpha Person\" = 0.5\n").unwrap();
+    let (people, unknown) = attendance.resolve(&result.voting_body, &[], &directory);
+    assert!(unknown.is_empty());
+    assert_eq!(people[0].probability, 0.5);
+
+    let mut meeting = Meeting::new(&result, options);
+    assert_eq!(meeting.arrive("Alpha Person").unwrap(), "A1");
+}
+
+#[test]
+fn test_call_in_suggestions_for_impossible_boards() {
+    let fum = make_board("FUM", "25/26", vec!["A", "B", "C", "D", "E", "F"]);
+    let x = make_board("X", "24/25", vec!["A", "B"]);
+    let y = make_board("Y", "24/25", vec!["C", "F"]);
+    let boards = vec![&x, &y];
+    let absent: HashSet<PersonId> = ["C", "D", "E"].map(PersonId::from_name).into();
+
+    let res = analyze_voting_groups(&fum, &boards, &absent);
+    assert_eq!(res.impossible.len(), 2);
+
+    let plan = suggest_call_ins(&fum, &res.impossible, &absent, &AnalysisOptions::default());
+    let ids = |call: &CallIn| match call {
+        CallIn::Found(v) => Some(
+            v.iter()
+                .map(|id| id.as_str().to_string())
+                .collect::<Vec<_>>(),
+        ),
+        _ => None,
+    };
+    // X behöver två av tre, Y en som inte är jävig (C är det).
+    assert_eq!(
+        ids(&plan.boards[0].call),
+        Some(vec!["c".into(), "d".into()])
+    );
+    assert_eq!(ids(&plan.boards[1].call), Some(vec!["d".into()]));
+    assert_eq!(ids(&plan.all), Some(vec!["c".into(), "d".into()]));
+
+    // Inte ens alla frånvarande räcker när alla är jäviga.
+    let z = make_board("Z", "24/25", vec!["A", "B", "C", "D", "E"]);
+    let boards = vec![&z];
+    let res = analyze_voting_groups(&fum, &boards, &absent);
+    let plan = suggest_call_ins(&fum, &res.impossible, &absent, &AnalysisOptions::default());
+    assert_eq!(plan.boards[0].call, CallIn::Unreachable);
+    assert_eq!(plan.all, CallIn::Unreachable);
+}
+
+#[test]
+fn test_call_in_search_exhausted_is_not_reported_as_unreachable() {
+    // 40 ordinarie med fast kvorum 10: p1–p20 är på plats, a1–a20 borta.
+    // K1 och K2 har 15 närvarande jäviga var och behöver 5 ojäviga till,
+    // K1 bland a11–a20 och K2 bland a1–a10. Var för sig räcker 5, men
+    // tillsammans krävs 10, och sökningen ger upp långt innan dess.
+    let present: Vec<String> = (1..=20).map(|i| format!("p{}", i)).collect();
+    let absent_names: Vec<String> = (1..=20).map(|i| format!("a{}", i)).collect();
+    let roster: Vec<&str> = present
+        .iter()
+        .chain(&absent_names)
+        .map(String::as_str)
+        .collect();
+    let fum = make_board("FUM", "25/26", roster.clone());
+    let conflicted = |absent: &[String]| {
+        let mut board = make_board("", "24/25", vec![]);
+        board.members = make_board("", "24/25", roster[..15].to_vec()).members;
+        board
+            .members
+            .extend(make_board("", "24/25", absent.iter().map(String::as_str).collect()).members);
+        board
+    };
+    let mut k1 = conflicted(&absent_names[..10]);
+    k1.name = "K1".to_string();
+    let mut k2 = conflicted(&absent_names[10..]);
+    k2.name = "K2".to_string();
+    let boards = vec![&k1, &k2];
+    let absent: HashSet<PersonId> = absent_names
+        .iter()
+        .map(|n| PersonId::from_name(n))
+        .collect();
+    let options = AnalysisOptions {
+        quorum_rule: QuorumRule::Fixed(10),
+        ..AnalysisOptions::default()
+    };
+
+    let res = analyze_voting_groups_with(&fum, &boards, &absent, &options);
+    assert_eq!(res.impossible.len(), 2);
+    let plan = suggest_call_ins(&fum, &res.impossible, &absent, &options);
+    let found = |call: &CallIn| -> Vec<String> {
+        match call {
+            CallIn::Found(v) => v.iter().map(|id| id.as_str().to_string()).collect(),
+            other => panic!("förväntade en mängd, fick {:?}", other),
+        }
+    };
+    assert_eq!(found(&plan.boards[0].call), absent_names[10..15]);
+    assert_eq!(found(&plan.boards[1].call), absent_names[..5]);
+    match &plan.all {
+        CallIn::SearchExhausted(everyone) => assert_eq!(everyone.len(), 20),
+        other => panic!("förväntade en avbruten sökning, fick {:?}", other),
+    }
+}
+
+// --- PARSERTESTER ---
+
+#[test]
+fn test_parse_text_boards_year_shorthand_and_multiline() {
+    let input = "FUM 2526,\nOrdförande: Anna\nAndersson\nLedamot: Bo Ek\nLedamot: vakant\nErsättare: Dan Lo\n\nKIDS 2024/2025,\nLedamot: Cecilia Ström\n";
+    let boards = parse_text_boards(input).unwrap().boards;
+
+    assert_eq!(boards.len(), 2);
+    assert_eq!(boards[0].name, "FUM");
+    assert_eq!(boards[0].year.to_string(), "2025/2026");
+    assert_eq!(boards[0].members[0].position, "Ordförande");
+    assert_eq!(boards[0].members[0].name, "Anna Andersson");
+    assert_eq!(boards[0].members[1].name, "Bo Ek");
+    assert!(boards[0].members[2].is_vacant());
+    assert_eq!(boards[0].deputies[0].name, "Dan Lo");
+    assert_eq!(boards[1].name, "KIDS");
+    assert_eq!(boards[1].year.to_string(), "2024/2025");
+}
+
+#[test]
+fn test_parse_text_boards_missing_year_is_inherited() {
+    let input = "ROST 2425,\nLedamot: A\nMårfin(t),\nLedamot: B";
+    let boards = parse_text_boards(input).unwrap().boards;
+
+    assert_eq!(boards.len(), 2);
+    assert_eq!(boards[1].name, "Mårfin(t)");
+    assert_eq!(boards[1].year.to_string(), "2024/2025");
+}
+
+#[test]
+fn test_parse_text_boards_wrapped_name_ending_in_comma() {
+    // "Andersson," följs inte av en post och är en del av namnet.
+    let input = "KIDS 2425,\nOrdförande: Anna\nAndersson,\n\nKPS 2425,\nLedamot: Bo Ek\n";
+    let parsed = parse_text_boards(input).unwrap();
+    assert_eq!(parsed.boards.len(), 2);
+    assert_eq!(parsed.boards[0].members[0].name, "Anna Andersson");
+
+    // Följs raden av en post går det inte att avgöra, så den blir en ny
+    // styrelse men med en varning.
+    let input = "KIDS 2425,\nOrdförande: Anna\nAndersson,\nLedamot: Bo Ek\n";
+    let parsed = parse_text_boards(input).unwrap();
+    assert_eq!(parsed.boards.len(), 2);
+    let kinds: Vec<_> = parsed.diagnostics.iter().map(|d| d.kind).collect();
+    assert_eq!(kinds, vec![DiagnosticKind::AmbiguousHeader]);
+    assert_eq!(parsed.diagnostics[0].level(), tracing::Level::WARN);
+}
+
+#[test]
+fn test_parse_text_boards_drops_duplicate_blocks() {
+    let input = "KIDS 2425,\nLedamot: A\nKIDS 2024/2025,\nLedamot: B\n";
+    let parsed = parse_text_boards(input).unwrap();
+    assert_eq!(parsed.boards.len(), 1);
+    assert_eq!(parsed.boards[0].members[0].name, "A");
+    assert_eq!(parsed.diagnostics[0].kind, DiagnosticKind::DuplicateBoard);
+    assert_eq!(
+        parsed.diagnostics[0].to_string(),
+        "Ignorerade dubblett: \"KIDS 2024/2025\""
+    );
+}
+
+#[test]
+fn test_parse_text_boards_rejects_text_before_first_header() {
+    assert!(parse_text_boards("Ledamot: A\nKIDS 2425,\n").is_err());
+}
+
+#[test]
+fn test_parse_html_boards_tabs() {
+    let html = r#"<section><h2>Förtroendevalda 2024/2025</h2>
+        <div class="elementor-widget-tabs">
+          <div class="elementor-tabs-wrapper"><div class="elementor-tab-desktop-title">KIDS</div></div>
+          <div class="elementor-tabs-content-wrapper">
+            <div class="elementor-tab-content"><p>Ordförande: Erik Nilsson</p><p>Ledamot: Anton Wall</p></div>
+          </div>
+        </div></section>"#;
+    let boards = parse_html_boards(html).unwrap().boards;
+
+    assert_eq!(boards.len(), 1);
+    assert_eq!(boards[0].year.to_string(), "2024/2025");
+    assert_eq!(boards[0].members.len(), 2);
+}
+
+#[test]
+fn test_parse_html_boards_short_form_years() {
+    let tab = |title: &str| {
+        format!(
+            r#"<div class="elementor-widget-tabs">
+              <div class="elementor-tabs-wrapper"><div class="elementor-tab-desktop-title">{}</div></div>
+              <div class="elementor-tabs-content-wrapper">
+                <div class="elementor-tab-content"><p>Ledamot: Anton Wall</p></div>
+              </div>
+            </div>"#,
+            title
+        )
+    };
+    let html = format!(
+        "<section><h2>Förtroendevalda 24/25</h2>{}</section>\
+         <section><h2>Styrelser verksamhetsåret 2526</h2>{}</section>",
+        tab("KIDS"),
+        tab("KPS")
+    );
+    let parsed = parse_html_boards(&html).unwrap();
+
+    let years: Vec<_> = parsed.boards.iter().map(|b| b.year.to_string()).collect();
+    assert_eq!(years.len(), 2);
+    assert!(years.contains(&"2024/2025".to_string()));
+    assert!(years.contains(&"2025/2026".to_string()));
+    assert!(parsed.diagnostics.is_empty());
+}
+
+#[test]
+fn test_parse_html_boards_ignores_other_numbers_in_headings() {
+    let html = r#"<section><h2>Styrelser 2024/2025</h2><h3>Kåren grundades 1962 och har 2021 medlemmar</h3>
+        <div class="elementor-widget-tabs">
+          <div class="elementor-tabs-wrapper"><div class="elementor-tab-desktop-title">KIDS</div></div>
+          <div class="elementor-tabs-content-wrapper">
+            <div class="elementor-tab-content"><p>Ledamot: Anton Wall</p></div>
+          </div>
+        </div></section>"#;
+    let boards = parse_html_boards(html).unwrap().boards;
+    assert_eq!(boards[0].year.to_string(), "2024/2025");
+
+    let html = html.replace("Styrelser 2024/2025", "Styrelser");
+    let parsed = parse_html_boards(&html).unwrap();
+    assert!(parsed.boards[0].year.is_unknown());
+    assert_eq!(parsed.diagnostics[0].kind, DiagnosticKind::UnknownYear);
+}
+
+#[test]
+fn test_parser_reports_skipped_content_as_diagnostics() {
+    let html = r#"<section>
+        <div class="elementor-widget-tabs">
+          <div class="elementor-tabs-wrapper">
+            <div class="elementor-tab-desktop-title">KIDS</div>
+            <div class="elementor-tab-desktop-title"></div>
+            <div class="elementor-tab-desktop-title">KIDS</div>
+            <div class="elementor-tab-desktop-title">Tom</div>
+          </div>
+          <div class="elementor-tabs-content-wrapper">
+            <div class="elementor-tab-content"><p>Vald av FUM</p><p>Ordförande: Erik</p><p>Nilsson</p></div>
+            <div class="elementor-tab-content"><p>Ledamot: A</p></div>
+            <div class="elementor-tab-content"><p>Ledamot: B</p></div>
+            <div class="elementor-tab-content"></div>
+          </div>
+        </div></section>"#;
+    let parsed = parse_html_boards(html).unwrap();
+    assert_eq!(parsed.boards.len(), 1);
+    assert_eq!(parsed.boards[0].members[0].name, "Erik Nilsson");
+
+    let kinds: Vec<_> = parsed.diagnostics.iter().map(|d| d.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::OrphanLine,
+            DiagnosticKind::ContinuationLine,
+            DiagnosticKind::UnknownYear,
+            DiagnosticKind::EmptyTitle,
+            DiagnosticKind::DuplicateBoard,
+            DiagnosticKind::SkippedTab,
+        ]
+    );
+    assert_eq!(
+        parsed.diagnostics[0].to_string(),
+        "Ignorerade rad utan kolon: section[0] > .elementor-widget-tabs[0] > \
+         .elementor-tab-desktop-title[0] \"KIDS\": \"Vald av FUM\""
+    );
+
+    let parsed = parse_text_boards("Okänd,\nLedamot: A\nKIDS 2425,\n").unwrap();
+    let kinds: Vec<_> = parsed.diagnostics.iter().map(|d| d.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![DiagnosticKind::UnknownYear, DiagnosticKind::SkippedTab]
+    );
+}
+
+#[test]
+fn test_roster_roundtrip_json_and_toml() {
+    let boards = vec![
+        make_board("KIDS", "2024/2025", vec!["Anton Wall"]),
+        make_board("FUM", "2025/2026", vec!["Erik Nilsson", "Nora Levin"]),
+    ];
+
+    for format in [RosterFormat::Json, RosterFormat::Toml] {
+        let text = render_roster(&boards, format).unwrap();
+        let mut parsed = parse_roster(&text, format).unwrap();
+        parsed.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(parsed, vec![boards[1].clone(), boards[0].clone()]);
+    }
+    assert_eq!(
+        RosterFormat::from_path("roster.TOML"),
+        Some(RosterFormat::Toml)
+    );
+    assert_eq!(RosterFormat::from_path("fum.txt"), None);
+}
+
+#[test]
+fn test_name_matcher_unicode_and_token_order() {
+    let m = NameMatcher::default();
+    // Versaler utanför ASCII, NFD-kodat "ö", bindestreck och omvänd ordning.
+    assert!(m.same("ÅSA LÖVSTRÖM", "Åsa Lövström"));
+    assert!(m.same("Åsa Lo\u{308}vstro\u{308}m", "Åsa Lövström"));
+    assert!(m.same("Anna-Karin  Berg", "anna karin berg"));
+    assert!(m.same("Nilsson Erik", "Erik Nilsson"));
+    assert!(!m.same("Åsa Lövström", "Asa Lovstrom"));
+    assert!(NameMatcher::new(true).same("Åsa Lövström", "Asa Lovstrom"));
+
+    // Två tecken som bara skiljer sig i accent kostar tillsammans en redigering.
+    assert_eq!(m.distance("Lovstrom", "Lövström"), 1);
+    assert_eq!(m.distance("Lovstrom", "Lavstram"), 2);
+    assert!(m.same("STRAUSS", "Strauß"));
+    assert_eq!(NameMatcher::new(true).distance("Lovstrom", "Lövström"), 0);
+    assert_eq!(m.distance("Nilson Erik", "Erik Nilsson"), 1);
+}
+
+#[test]
+fn test_voting_logic_names_match_across_spellings() {
+    // Samma person skrivs olika i FUM och i styrelsen, och frånvaron anges
+    // med en tredje stavning. Visningsnamnet tas från FUM-listan.
+    let fum = make_board(
+        "FUM",
+        "24/25",
+        vec!["Åsa Lövström", "Erik Nilsson", "C", "D"],
+    );
+    let b1 = make_board("StyrelseA", "23/24", vec!["ÅSA  LÖVSTRÖM", "Nilsson Erik"]);
+    let all = vec![&b1];
+
+    let res = analyze_voting_groups(&fum, &all, &HashSet::new());
+    let conflicts: Vec<_> = res
+        .conflicts_of(&b1)
+        .iter()
+        .map(|id| res.name_of(id))
+        .collect();
+    assert_eq!(conflicts, vec!["Åsa Lövström", "Erik Nilsson"]);
+
+    let mut absent = HashSet::new();
+    absent.insert(PersonId::from_name("åsa lövström"));
+    let res = analyze_voting_groups(&fum, &all, &absent);
+    assert_eq!(res.present_count, 3);
+}
+
+#[test]
+fn test_corrections_suggest_replay_and_apply() {
+    let fum_names = vec!["Erik Nilsson".to_string(), "Erika Nilsson".to_string()];
+    let mut boards = vec![
+        make_board("KIDS", "2024/2025", vec!["Eric Nilson", "Anton Wall"]),
+        make_board("KIDS", "2023/2024", vec!["Eric Nilson"]),
+    ];
+    let directory = Directory::default();
+
+    // Båda FUM-namnen ligger nära, det närmaste föreslås först.
+    let suggestions = find_suggestions(
+        &boards,
+        &fum_names,
+        &directory,
+        &[VerksamhetsAr::split(2024)],
+        3,
+    );
+    assert_eq!(suggestions.len(), 1);
+    let names: Vec<_> = suggestions[0]
+        .candidates
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["Erik Nilsson", "Erika Nilsson"]);
+
+    let log: DecisionLog = toml::from_str(
+        r#"
+        [[correction]]
+        board = "KIDS"
+        year = "2024/2025"
+        found = "eric nilson"
+        replace_with = "Erika Nilsson"
+        "#,
+    )
+    .unwrap();
+    let decision = log.find(&suggestions[0], &directory).unwrap();
+    assert_eq!(decision.replace_with.as_deref(), Some("Erika Nilsson"));
+
+    let mut rejected = log.clone();
+    // Samma förslag med annan stavning ersätter det tidigare beslutet.
+    rejected.record(
+        Decision {
+            found: "Eric  NILSON".into(),
+            replace_with: None,
+            ..decision.clone()
+        },
+        &directory,
+    );
+    assert_eq!(rejected.decisions.len(), 1);
+    assert_eq!(
+        rejected
+            .find(&suggestions[0], &directory)
+            .unwrap()
+            .replace_with,
+        None
+    );
+
+    apply_corrections(
+        &mut boards,
+        &[Correction {
+            board: "KIDS".into(),
+            year: VerksamhetsAr::split(2024),
+            from: "Eric Nilson".into(),
+            to: "Erika Nilsson".into(),
+            distance: 2,
+        }],
+    );
+    assert_eq!(boards[0].members[0].name, "Erika Nilsson");
+    assert_eq!(boards[1].members[0].name, "Eric Nilson");
+}
+
+#[test]
+fn test_corrections_cover_deputies() {
+    let fum_names = vec!["Erik Nilsson".to_string()];
+    let mut kids = make_board("KIDS", "2024/2025", vec!["Anton Wall"]);
+    kids.deputies = make_board("", "Okänt år", vec!["Eric Nilson"]).members;
+    let mut boards = vec![kids];
+
+    let suggestions = find_suggestions(
+        &boards,
+        &fum_names,
+        &Directory::default(),
+        &[VerksamhetsAr::split(2024)],
+        3,
+    );
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].found, "Eric Nilson");
+
+    apply_corrections(
+        &mut boards,
+        &[Correction {
+            board: "KIDS".into(),
+            year: VerksamhetsAr::split(2024),
+            from: "Eric Nilson".into(),
+            to: "Erik Nilsson".into(),
+            distance: 2,
+        }],
+    );
+    assert_eq!(boards[0].deputies[0].name, "Erik Nilsson");
+}
+
+#[test]
+fn test_report_json_contains_groups_conflicts_and_presence() {
+    let fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D"]);
+    let b1 = make_board("StyrelseA", "23/24", vec!["A"]);
+    let b2 = make_board("StyrelseB", "23/24", vec!["A", "B", "C"]);
+    let all = vec![&b1, &b2];
+
+    let mut absent = HashSet::new();
+    absent.insert(PersonId::from_name("A"));
+    let res = analyze_voting_groups(&fum, &all, &absent);
+    let report = Report::new(&res, &fum, &absent, &[]);
+
+    assert!(report.quorate);
+    assert_eq!(report.groups.len(), 1);
+    assert_eq!(report.groups[0].boards[0].name, "StyrelseA");
+    assert_eq!(report.impossible[0].name, "StyrelseB");
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(json["quorum_rule"], "half");
+    assert_eq!(json["groups"][0]["conflicted"][0]["name"], "A");
+    assert_eq!(json["groups"][0]["conflicted"][0]["present"], false);
+    assert_eq!(
+        json["impossible"][0]["conflicts"].as_array().unwrap().len(),
+        3
+    );
+}
+
+#[test]
+fn test_protocol_markdown_paragraphs_and_template_override() {
+    // A är frånvarande och behöver inte lämna lokalen, B anmäler jäv.
+    let fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D"]);
+    let b1 = make_board("StyrelseA", "23/24", vec!["A", "B"]);
+    let b2 = make_board("StyrelseB", "23/24", vec!["C"]);
+    let all = vec![&b1, &b2];
+
+    let mut absent = HashSet::new();
+    absent.insert(PersonId::from_name("A"));
+    let res = analyze_voting_groups(&fum, &all, &absent);
+    let report = Report::new(&res, &fum, &absent, &[]);
+
+    let markdown = ProtocolTemplate::default().render(&report);
+    assert!(markdown.contains("## §1 Ansvarsfrihet för StyrelseA (2023/2024)"));
+    assert!(markdown.contains("## §2 Ansvarsfrihet för StyrelseB (2023/2024)"));
+    assert!(markdown.contains("lämnar lokalen:\n- B\n"));
+    assert!(markdown.contains("Kvarvarande röstberättigade (2 st):\n- C\n- D\n"));
+
+    let template: ProtocolTemplate = toml::from_str(
+        r#"
+        first_paragraph = 14
+        header = ""
+        group = "§{paragraph}: {boards} [{voters}]\n"
+        empty_list = "-"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        template.render(&report),
+        "§14: StyrelseA (2023/2024) [- C\n- D]\n§15: StyrelseB (2023/2024) [- B\n- D]\n"
+    );
+}
+
+#[test]
+fn test_dot_export_edges_groups_and_absence() {
+    let fum = make_board("FUM", "24/25", vec!["A", "B", "C", "D"]);
+    let b1 = make_board("StyrelseA", "23/24", vec!["A"]);
+    let b2 = make_board("StyrelseB", "23/24", vec!["A", "B", "C"]);
+    let all = vec![&b1, &b2];
+
+    let mut absent = HashSet::new();
+    absent.insert(PersonId::from_name("A"));
+    let res = analyze_voting_groups(&fum, &all, &absent);
+    let dot = render_dot(&Report::new(&res, &fum, &absent, &[]));
+
+    assert!(dot.starts_with("graph jav {"));
+    assert!(dot.contains(r#""person:a" [label="A (2)", style=dashed, penwidth=2];"#));
+    assert!(dot.contains(r#"label="StyrelseA\n2023/2024\nGrupp 1""#));
+    assert!(dot.contains(r#"label="StyrelseB\n2023/2024\nKan ej behandlas""#));
+    assert_eq!(dot.matches(" -- ").count(), 4);
+}
+
+#[test]
+fn test_people_history_and_roster_diff() {
+    let kids_24 = make_board("KIDS", "2024/2025", vec!["Erik Nilsson", "Anna Berg"]);
+    let fum_25 = make_board("FUM", "2025/2026", vec!["NILSSON Erik"]);
+    let boards = vec![fum_25.clone(), kids_24.clone()];
+
+    let people = people_history(&boards, &Directory::default());
+    let names: Vec<_> = people.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["Anna Berg", "NILSSON Erik"]);
+    let years: Vec<_> = people[1].roles.iter().map(|r| r.board.as_str()).collect();
+    assert_eq!(years, vec!["KIDS", "FUM"]);
+
+    // Ny stavning räknas inte som ändring, ny post och ny person gör det.
+    let mut kids_new = make_board(
+        "KIDS",
+        "2024/2025",
+        vec!["erik nilsson", "Anna Berg", "Bo Ek"],
+    );
+    kids_new.members[1].position = "Kassör".to_string();
+    let diff = diff_rosters(&boards, &[kids_new], &Directory::default());
+    assert_eq!(
+        diff.removed_boards,
+        vec![("FUM".to_string(), VerksamhetsAr::split(2025))]
+    );
+    assert!(diff.added_boards.is_empty());
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].added[0].name, "Bo Ek");
+    assert_eq!(
+        diff.changed[0].moved,
+        vec![(
+            "Anna Berg".to_string(),
+            "Ledamot".to_string(),
+            "Kassör".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_find_people_by_alias_and_fuzzy_name() {
+    let boards = vec![
+        make_board("KIDS", "2023/2024", vec!["Eric Nilson", "Anna Berg"]),
+        make_board("FUM", "2025/2026", vec!["Erik Nilsson", "Anna Bergh"]),
+    ];
+    let directory = Directory::parse(
+        r#"
+        [[person]]
+        id = "anna"
+        name = "Anna Berg"
+        aliases = ["Anna Bergh", "Annie"]
+        "#,
+    )
+    .unwrap();
+    let people = people_history(&boards, &directory);
+
+    // Alias ger en exakt träff med båda styrelserna.
+    let hits = find_people(&people, "annie", &directory, 3);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].1, 0);
+    assert_eq!(hits[0].0.roles.len(), 2);
+
+    // Stavfel ger ungefärliga träffar, närmast först.
+    let hits = find_people(&people, "Erik Nilson", &directory, 3);
+    let names: Vec<_> = hits.iter().map(|(p, d)| (p.name.as_str(), *d)).collect();
+    assert_eq!(names, vec![("Eric Nilson", 1), ("Erik Nilsson", 1)]);
+    assert!(find_people(&people, "Bo Ek", &directory, 1).is_empty());
+}
+
+#[test]
+fn test_source_errors_have_distinct_exit_codes() {
+    let missing = load_boards("finns/inte/fum.txt").unwrap_err();
+    assert!(matches!(missing, Error::Fetch { .. }));
+    assert_eq!(missing.exit_code(), 3);
+
+    let broken = parse_source("roster.json", "{ inte json").unwrap_err();
+    assert!(matches!(broken, Error::Parse { .. }));
+    assert_eq!(broken.exit_code(), 4);
+
+    let not_quorate = Error::NotQuorate {
+        present: 3,
+        required: 5,
+    };
+    assert_eq!(not_quorate.exit_code(), 10);
+    assert_eq!(
+        not_quorate.to_string(),
+        "Mötet är inte beslutsmässigt: 3 närvarande, 5 krävs"
+    );
+}
+
+#[test]
+fn test_audit_builder_runs_whole_pipeline() {
+    let boards = vec![
+        make_board(
+            "Fullmäktige",
+            "2025/2026",
+            vec!["Erik Nilsson", "Anna Berg", "Cecilia Ek", "David Lund"],
+        ),
+        make_board("KIDS", "2024/2025", vec!["Eric Nilson", "Anna Berg"]),
+        make_board("Sexmästeriet", "2024/2025", vec!["Cecilia Ek"]),
+        make_board("Gammal", "2023/2024", vec!["David Lund"]),
+    ];
+
+    let mut asked = Vec::new();
+    let result = Audit::new(boards.clone())
+        .absent(["anna berg", "Okänd Person"])
+        .priority(["sexmästeriet"])
+        .decide_corrections(|s| {
+            asked.push(s.found.clone());
+            Ok(None)
+        })
+        .run()
+        .unwrap();
+    assert_eq!(asked, vec!["Eric Nilson"]);
+
+    assert_eq!(result.audited_years, vec![VerksamhetsAr::split(2024)]);
+    assert_eq!(result.absent, vec!["Anna Berg"]);
+    assert_eq!(result.unknown_absent, vec!["Okänd Person"]);
+    assert_eq!(result.suggestions.len(), 1);
+    assert!(result.corrections.is_empty());
+    let audited: Vec<_> = result.boards.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(audited, vec!["Sexmästeriet", "KIDS"]);
+    assert_eq!(result.report.present_count, 3);
+    assert!(result.report.quorate);
+
+    // Standardbeslutet godkänner det närmaste namnet.
+    let result = Audit::new(boards).run().unwrap();
+    assert_eq!(result.corrections[0].to, "Erik Nilsson");
+    assert_eq!(result.report.corrections.len(), 1);
+}
+
+#[test]
+fn test_audit_several_years_in_one_run() {
+    let boards = vec![
+        make_board("Fullmäktige", "2025/2026", vec!["A", "B", "C", "D"]),
+        make_board("KIDS", "2024/2025", vec!["A"]),
+        make_board("KIDS", "2023/2024", vec!["A", "B"]),
+        make_board("KPS", "2022/2023", vec!["C"]),
+    ];
+    let range: YearRange = "2023/2024..2024/2025".parse().unwrap();
+    let result = Audit::new(boards.clone())
+        .audit_years(range.years())
+        .run()
+        .unwrap();
+
+    let audited: Vec<_> = result
+        .boards
+        .iter()
+        .map(|b| format!("{} {}", b.name, b.year))
+        .collect();
+    assert_eq!(audited, vec!["KIDS 2024/2025", "KIDS 2023/2024"]);
+    assert_eq!(
+        result.report.audited_years,
+        vec![VerksamhetsAr::split(2023), VerksamhetsAr::split(2024)]
+    );
+    // Båda årens KIDS har A som jävig och hamnar i samma grupp.
+    assert_eq!(result.report.groups.len(), 1);
+    let group = &result.report.groups[0];
+    assert_eq!(group.years, result.report.audited_years);
+    assert_eq!(group.boards[0].year, VerksamhetsAr::split(2023));
+
+    // Ett år utan styrelser är ett fel, inte en tom granskning.
+    let err = Audit::new(boards)
+        .audit_years([VerksamhetsAr::split(2020), VerksamhetsAr::split(2024)])
+        .run()
+        .unwrap_err();
+    assert!(matches!(err, Error::NoBoardsForYear(y) if y == VerksamhetsAr::split(2020)));
+
+    assert!("2024/2025..2023/2024".parse::<YearRange>().is_err());
+    assert!("2023..2024/2025".parse::<YearRange>().is_err());
+}
+
+#[test]
+fn test_meeting_replans_remaining_boards_after_changes() {
+    let boards = vec![
+        make_board("Fullmäktige", "2025/2026", vec!["A", "B", "C", "D", "E"]),
+        make_board("KIDS", "2024/2025", vec!["A", "B"]),
+        make_board("KPS", "2024/2025", vec!["C"]),
+    ];
+    let result = Audit::new(boards).run().unwrap();
+    let mut meeting = Meeting::new(&result, AnalysisOptions::default());
+    assert_eq!(meeting.plan().groups.len(), 2);
+
+    // Med D borta räcker inte rösterna till KIDS längre.
+    assert_eq!(meeting.leave("d").unwrap(), "D");
+    assert!(meeting.leave("D").is_err());
+    assert!(meeting.leave("Okänd").is_err());
+    assert_eq!(meeting.plan().present_count, 4);
+    let impossible: Vec<_> = meeting.plan().impossible.iter().map(|b| &b.name).collect();
+    assert_eq!(impossible, ["KIDS"]);
+
+    let decided = meeting.done(1).unwrap();
+    assert_eq!(decided.number, 1);
+    assert_eq!(decided.group.boards[0].name, "KPS");
+    assert!(meeting.done(7).is_err());
+
+    // D kommer tillbaka: bara KIDS planeras, KPS ligger fast som beslutad.
+    meeting.arrive("D").unwrap();
+    assert_eq!(meeting.plan().groups.len(), 1);
+    assert_eq!(meeting.plan().groups[0].boards[0].name, "KIDS");
+    assert_eq!(meeting.decided().len(), 1);
+    meeting.done(1).unwrap();
+    assert!(meeting.is_finished());
+
+    // Besluten prövas inte om, men de som ändringen berör markeras. D var
+    // borta när KPS beslutades och berör bara KIDS-beslutet.
+    assert!(meeting.decided().iter().all(|d| d.touched.is_none()));
+    meeting.leave("D").unwrap();
+    let touched: Vec<_> = meeting
+        .decided()
+        .iter()
+        .map(|d| d.touched.clone())
+        .collect();
+    assert_eq!(touched, [None, Some(Involvement::Voter("D".to_string()))]);
+    meeting.leave("A").unwrap();
+    let touched: Vec<_> = meeting
+        .decided()
+        .iter()
+        .map(|d| d.touched.clone())
+        .collect();
+    assert_eq!(
+        touched,
+        [
+            Some(Involvement::Voter("A".to_string())),
+            Some(Involvement::Conflicted("A".to_string()))
+        ]
+    );
+
+    assert_eq!(
+        "done group 2".parse::<MeetingCommand>(),
+        Ok(MeetingCommand::Done(2))
+    );
+    assert_eq!(
+        "arrive  Anna Berg ".parse::<MeetingCommand>(),
+        Ok(MeetingCommand::Arrive("Anna Berg".to_string()))
+    );
+    assert!("leave".parse::<MeetingCommand>().is_err());
+}
+
+#[test]
+fn test_audit_reports_missing_voting_body_and_bad_year() {
+    let boards = vec![make_board("Fullmäktige", "Okänt år", vec!["A"])];
+    let err = Audit::new(boards.clone())
+        .base_board("Styrelsen")
+        .run()
+        .unwrap_err();
+    assert_eq!(err.exit_code(), 6);
+
+    let err = Audit::new(boards).run().unwrap_err();
+    assert!(matches!(err, Error::InvalidYear(_)));
+
+    // Kalenderår har ett föregående år men inga granskade styrelser här.
+    let boards = vec![make_board("Fullmäktige", "2025", vec!["A"])];
+    let err = Audit::new(boards).run().unwrap_err();
+    assert!(matches!(
+        err,
+        Error::NoBoardsForYear(VerksamhetsAr::Calendar(2024))
+    ));
+}
+
+#[test]
+fn test_verksamhetsar_parsing_ordering_and_arithmetic() {
+    let parse = |s: &str| s.parse::<VerksamhetsAr>().unwrap();
+    for text in ["2024/2025", "24/25", "2425", " 2024 / 2025 "] {
+        assert_eq!(parse(text), VerksamhetsAr::split(2024), "{}", text);
+    }
+    assert_eq!(parse("2025"), VerksamhetsAr::Calendar(2025));
+
+    // Kalenderår som ser ut som en förkortning skrivs med prefix och läses
+    // tillbaka som samma år.
+    assert_eq!(VerksamhetsAr::Calendar(2021).to_string(), "kalenderår 2021");
+    assert_eq!(parse("Kalenderår 2021"), VerksamhetsAr::Calendar(2021));
+    for year in [VerksamhetsAr::Calendar(2021), VerksamhetsAr::Calendar(2025)] {
+        let json = serde_json::to_string(&year).unwrap();
+        assert_eq!(serde_json::from_str::<VerksamhetsAr>(&json).unwrap(), year);
+    }
+    assert_eq!(parse("okänt år"), VerksamhetsAr::Unknown);
+    assert_eq!(parse("2425").to_string(), "2024/2025");
+    assert!("2024/2026".parse::<VerksamhetsAr>().is_err());
+    assert!("vt25".parse::<VerksamhetsAr>().is_err());
+
+    // Sorteras efter startdatum, inte som text.
+    let mut years = [
+        parse("2025/2026"),
+        parse("2025"),
+        parse("Okänt år"),
+        parse("2024/2025"),
+        parse("2024"),
+    ];
+    years.sort();
+    let sorted: Vec<String> = years.iter().map(|y| y.to_string()).collect();
+    assert_eq!(
+        sorted,
+        ["Okänt år", "2024", "2024/2025", "2025", "2025/2026"]
+    );
+
+    assert_eq!(parse("2025/2026").previous(), Some(parse("2024/2025")));
+    assert_eq!(parse("2025").next(), Some(parse("2026")));
+    assert_eq!(VerksamhetsAr::Unknown.previous(), None);
+}